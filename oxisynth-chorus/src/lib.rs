@@ -164,13 +164,12 @@ impl Chorus {
         self.number_blocks = self.new_number_blocks;
     }
 
-    pub fn process_mix(
-        &mut self,
-        in_0: &mut [f32; 64],
-        left_out: &mut [f32; 64],
-        right_out: &mut [f32; 64],
-    ) {
-        for sample_index in 0..64 {
+    /// Process `in_0` and add the result to `left_out` and `right_out`.
+    ///
+    /// The buffers can have any length, the shortest one is processed.
+    pub fn process_mix(&mut self, in_0: &mut [f32], left_out: &mut [f32], right_out: &mut [f32]) {
+        let len = in_0.len().min(left_out.len()).min(right_out.len());
+        for sample_index in 0..len {
             let d_in = in_0[sample_index];
             let mut d_out = 0.0;
             self.chorusbuf[self.counter as usize] = d_in;
@@ -208,8 +207,12 @@ impl Chorus {
         }
     }
 
-    pub fn process_replace(&mut self, left_out: &mut [f32; 64], right_out: &mut [f32; 64]) {
-        for sample_index in 0..64 {
+    /// Process `left_out` in place, and write the same result to `right_out`.
+    ///
+    /// The buffers can have any length, the shortest one is processed.
+    pub fn process_replace(&mut self, left_out: &mut [f32], right_out: &mut [f32]) {
+        let len = left_out.len().min(right_out.len());
+        for sample_index in 0..len {
             // Don't ask me why only left buf is considered an input...
             let d_in = left_out[sample_index];
             let mut d_out = 0.0;
@@ -244,8 +244,12 @@ impl Reverb {
         ];
     }
 
-    pub fn process_replace(&mut self, left_out: &mut [f32; 64], right_out: &mut [f32; 64]) {
-        for k in 0..64 {
+    /// Process `left_out` in place, and write the right channel to `right_out`.
+    ///
+    /// The buffers can have any length, the shortest one is processed.
+    pub fn process_replace(&mut self, left_out: &mut [f32], right_out: &mut [f32]) {
+        let len = left_out.len().min(right_out.len());
+        for k in 0..len {
             let mut out_r = 0f32;
             let mut out_l = 0f32;
 
@@ -270,13 +274,12 @@ impl Reverb {
         }
     }
 
-    pub fn process_mix(
-        &mut self,
-        in_0: &mut [f32; 64],
-        left_out: &mut [f32; 64],
-        right_out: &mut [f32; 64],
-    ) {
-        for k in 0..64 {
+    /// Process `in_0` and add the result to `left_out` and `right_out`.
+    ///
+    /// The buffers can have any length, the shortest one is processed.
+    pub fn process_mix(&mut self, in_0: &mut [f32], left_out: &mut [f32], right_out: &mut [f32]) {
+        let len = in_0.len().min(left_out.len()).min(right_out.len());
+        for k in 0..len {
             let mut out_r = 0f32;
             let mut out_l = out_r;
             let input = (2.0 * in_0[k] + DC_OFFSET) * self.gain;
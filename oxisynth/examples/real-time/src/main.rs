@@ -3,7 +3,13 @@ use std::{error::Error, path::Path, sync::mpsc::Receiver};
 
 use midir::MidiInput;
 
-use oxisynth::MidiEvent;
+use oxisynth::{MidiEvent, MidiMessage, MidiParser};
+
+/// Message sent from the MIDI input to the audio thread
+enum SynthMessage {
+    Event(MidiEvent),
+    SysEx(Vec<u8>),
+}
 
 pub struct SynthBackend {
     _host: cpal::Host,
@@ -35,7 +41,7 @@ impl SynthBackend {
         })
     }
 
-    fn run<T: cpal::Sample>(&self, rx: Receiver<MidiEvent>, path: &Path) -> cpal::Stream {
+    fn run<T: cpal::Sample>(&self, rx: Receiver<SynthMessage>, path: &Path) -> cpal::Stream {
         let mut synth = {
             let sample_rate = self.stream_config.sample_rate.0 as f32;
 
@@ -59,8 +65,14 @@ impl SynthBackend {
         let mut next_value = move || {
             let (l, r) = synth.read_next();
 
-            if let Ok(e) = rx.try_recv() {
-                synth.send_event(e).ok();
+            match rx.try_recv() {
+                Ok(SynthMessage::Event(e)) => {
+                    synth.send_event(e).ok();
+                }
+                Ok(SynthMessage::SysEx(data)) => {
+                    synth.send_sysex(&data).ok();
+                }
+                Err(_) => {}
             }
 
             (l, r)
@@ -100,7 +112,7 @@ impl SynthBackend {
         &mut self,
         path: &P,
     ) -> (cpal::Stream, SynthOutputConnection) {
-        let (tx, rx) = std::sync::mpsc::channel::<MidiEvent>();
+        let (tx, rx) = std::sync::mpsc::channel::<SynthMessage>();
         let _stream = match self.sample_format {
             cpal::SampleFormat::F32 => self.run::<f32>(rx, path.as_ref()),
             cpal::SampleFormat::I16 => self.run::<i16>(rx, path.as_ref()),
@@ -112,24 +124,16 @@ impl SynthBackend {
 }
 
 pub struct SynthOutputConnection {
-    tx: std::sync::mpsc::Sender<MidiEvent>,
+    tx: std::sync::mpsc::Sender<SynthMessage>,
 }
 
 impl SynthOutputConnection {
-    fn note_on(&mut self, channel: u8, key: u8, vel: u8) {
-        self.tx.send(MidiEvent::NoteOn { channel, key, vel }).ok();
-    }
-    fn note_off(&mut self, channel: u8, key: u8) {
-        self.tx.send(MidiEvent::NoteOff { channel, key }).ok();
+    fn send_event(&mut self, event: MidiEvent) {
+        self.tx.send(SynthMessage::Event(event)).ok();
     }
-    fn cc(&mut self, channel: u8, ctrl: u8, value: u8) {
-        self.tx
-            .send(MidiEvent::ControlChange {
-                channel,
-                ctrl,
-                value,
-            })
-            .ok();
+
+    fn send_sysex(&mut self, data: &[u8]) {
+        self.tx.send(SynthMessage::SysEx(data.to_vec())).ok();
     }
 }
 
@@ -175,31 +179,26 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let (_stream, mut synth_conn) = synth.new_output_connection(&"./testdata/sin.sf2");
 
+    let mut parser = MidiParser::new();
+
     // _conn_in needs to be a named parameter, because it needs to be kept alive until the end of the scope
     let _conn_in = midi_in.connect(
         in_port,
         "midir-read-input",
         move |stamp, message, _| {
-            if message.len() == 3 {
-                let note = message[1];
-                if (21..=108).contains(&note) {
-                    if message[0] == 128 || message[2] == 0 {
-                        println!("NoteOff {}", message[1]);
-                        synth_conn.note_off(0, message[1]);
-                        // tx.send((false, message[1], message[2])).unwrap();
-                    } else if message[0] == 144 {
-                        println!("NoteOn {},{}", message[1], message[2]);
-                        synth_conn.note_on(0, message[1], message[2]);
-                        // required_notes.lock().unwrap().remove(&note);
-                        // tx.send((true, message[1], message[2])).unwrap();
-                    }
+            println!("{}: {:?} (len = {})", stamp, message, message.len());
+
+            parser.feed(message, |msg| match msg {
+                MidiMessage::Event(event) => {
+                    println!("{:?}", event);
+                    synth_conn.send_event(event);
                 }
-                if message[0] == 177 {
-                    synth_conn.cc(0, message[1] as _, message[2] as _);
-                    println!("Sustain {}", if message[2] != 0 { "On" } else { "Off" });
+                MidiMessage::SysEx(data) => {
+                    println!("SysEx {:?}", data);
+                    synth_conn.send_sysex(data);
                 }
-                println!("{}: {:?} (len = {})", stamp, message, message.len());
-            }
+                MidiMessage::System(_) => {}
+            });
         },
         (),
     )?;
@@ -1,7 +1,7 @@
 use crate::{
     core::midi,
     error::{range_check, OxiError},
-    MidiEvent, SoundFontId, Synth,
+    MidiEvent, NotePriority, SoundFontId, Synth,
 };
 
 /// MIDI related
@@ -10,6 +10,20 @@ impl Synth {
         crate::core::midi::handle_event(&mut self.core, event)
     }
 
+    /// Send a SysEx message, with or without the `F0`/`F7` framing.
+    ///
+    /// The universal messages are supported:
+    /// - GM1/GM2 System On, that resets the synth like [`MidiEvent::SystemReset`]
+    /// - Master Volume, Master Balance, Master Fine Tuning and Master Coarse Tuning
+    /// - MIDI Tuning Standard bulk dumps, single note tuning changes and scale/octave tunings,
+    ///   see [`Synth::tuning`]
+    ///
+    /// Messages addressed to an other device ID, see [`SynthDescriptor::device_id`](crate::SynthDescriptor::device_id),
+    /// or that are not supported are ignored.
+    pub fn send_sysex(&mut self, data: &[u8]) -> Result<(), OxiError> {
+        crate::core::sysex::handle_sysex(&mut self.core, data)
+    }
+
     /// Returns the number of MIDI channels that the synthesizer uses internally
     pub fn channel_count(&self) -> usize {
         self.core.channels.len()
@@ -43,6 +57,67 @@ impl Synth {
         Ok(channel.pitch_wheel_sensitivity())
     }
 
+    /// Set which of the held keys sounds when the channel is in mono mode.
+    ///
+    /// The channel is in mono mode after a Mono Mode On message (CC126), until a Poly Mode On
+    /// message (CC127), and while the legato footswitch (CC68) is down.
+    /// Each new note is retriggered, except with the legato footswitch down.
+    pub fn set_note_priority(
+        &mut self,
+        channel: u8,
+        note_priority: NotePriority,
+    ) -> Result<(), OxiError> {
+        let channel = self.core.channels.get_mut(channel as usize)?;
+        channel.set_note_priority(note_priority);
+        Ok(())
+    }
+
+    /// Get the note priority of the mono mode.
+    pub fn note_priority(&self, channel: u8) -> Result<NotePriority, OxiError> {
+        let channel = self.core.channels.get(channel as usize)?;
+        Ok(channel.note_priority())
+    }
+
+    /// Route a MIDI channel to an audio group.
+    ///
+    /// By default the channel `n` is in the group `n % audio_groups`,
+    /// see [`SynthDescriptor::audio_groups`](crate::SynthDescriptor::audio_groups).
+    /// The routing is kept across system resets.
+    pub fn set_channel_output(&mut self, channel: u8, group: u8) -> Result<(), OxiError> {
+        if group >= self.core.settings.audio_groups {
+            return Err(OxiError::AudioGroupOutOfRange);
+        }
+        let channel = self.core.channels.get_mut(channel as usize)?;
+        channel.set_audio_group(group as usize);
+        Ok(())
+    }
+
+    /// Get the audio group of a MIDI channel.
+    pub fn channel_output(&self, channel: u8) -> Result<u8, OxiError> {
+        let channel = self.core.channels.get(channel as usize)?;
+        Ok(channel.audio_group(self.core.settings.audio_groups as usize) as u8)
+    }
+
+    /// Choose whether the voices of a MIDI channel are sent to the reverb and chorus buses.
+    ///
+    /// Both are enabled by default. The routing is kept across system resets.
+    pub fn set_channel_fx_buses(
+        &mut self,
+        channel: u8,
+        reverb: bool,
+        chorus: bool,
+    ) -> Result<(), OxiError> {
+        let channel = self.core.channels.get_mut(channel as usize)?;
+        channel.set_fx_sends(reverb, chorus);
+        Ok(())
+    }
+
+    /// Get whether the voices of a MIDI channel are sent to the reverb and chorus buses.
+    pub fn channel_fx_buses(&self, channel: u8) -> Result<(bool, bool), OxiError> {
+        let channel = self.core.channels.get(channel as usize)?;
+        Ok((channel.reverb_send(), channel.chorus_send()))
+    }
+
     /// Select a bank.
     pub fn select_bank(&mut self, channel: u8, bank: u32) -> Result<(), OxiError> {
         self.core
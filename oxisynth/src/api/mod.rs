@@ -4,4 +4,5 @@ mod settings;
 mod tuning;
 mod write;
 
-pub use tuning::Tuning;
+pub use tuning::{KeyboardMapping, Scale, Tuning};
+pub use write::{Stems, StereoBuffer};
@@ -14,7 +14,7 @@ impl Synth {
     pub fn set_gain(&mut self, gain: f32) {
         let gain = gain.clamp(0.0, 10.0);
         self.core.settings.gain = gain;
-        self.core.voices.set_gain(gain)
+        self.core.voices.set_gain(self.core.gain())
     }
 
     /// Get the master gain
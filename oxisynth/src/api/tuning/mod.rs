@@ -0,0 +1,209 @@
+mod scala;
+
+use crate::core::ModulateCtrl;
+use crate::OxiError;
+
+pub use scala::{KeyboardMapping, Scale};
+
+/// Tuning
+impl crate::Synth {
+    /// Select a tuning for a channel.
+    ///
+    /// The tuning is not part of the tuning bank, use [`Synth::select_tuning`] for those.
+    /// Only the next notes use the new tuning, see [`Self::set_tuning_applied`].
+    pub fn set_tuning(&mut self, chan: u8, tuning: Option<Tuning>) -> Result<(), OxiError> {
+        let channel = self.core.channels.get_mut(chan as usize)?;
+        channel.set_tuning(tuning);
+        Ok(())
+    }
+
+    /// Same as [`Self::set_tuning`], but the notes that are playing on the channel
+    /// are retuned right away.
+    pub fn set_tuning_applied(&mut self, chan: u8, tuning: Option<Tuning>) -> Result<(), OxiError> {
+        self.set_tuning(chan, tuning)?;
+        self.retune_channel(chan);
+        Ok(())
+    }
+
+    /// Add a tuning to the tuning bank, or replace the existing one.
+    ///
+    /// Channels that use this bank and program are updated.
+    /// Tunings can also be added with MIDI Tuning Standard SysEx messages, see [`Synth::send_sysex`].
+    pub fn define_tuning(&mut self, bank: u8, program: u8, name: &str, tuning: Tuning) {
+        self.core
+            .tunings
+            .insert(bank, program, name.to_owned(), tuning);
+        self.core.refresh_tuning(bank, program, false);
+    }
+
+    /// Same as [`Self::define_tuning`], but the notes that are playing on the updated
+    /// channels are retuned right away.
+    pub fn define_tuning_applied(&mut self, bank: u8, program: u8, name: &str, tuning: Tuning) {
+        self.core
+            .tunings
+            .insert(bank, program, name.to_owned(), tuning);
+        self.core.refresh_tuning(bank, program, true);
+    }
+
+    /// Remove a tuning from the tuning bank.
+    ///
+    /// Channels that use it keep their current tuning, until an other one is selected.
+    pub fn remove_tuning(&mut self, bank: u8, program: u8) -> Option<Tuning> {
+        self.core.tunings.remove(bank, program).map(|e| e.tuning)
+    }
+
+    /// Iterate over the bank, program and name of the tunings of the tuning bank.
+    pub fn tunings(&self) -> impl Iterator<Item = (u8, u8, &str)> {
+        self.core
+            .tunings
+            .iter()
+            .map(|((bank, program), e)| (bank, program, e.name.as_str()))
+    }
+
+    /// Select a tuning of the tuning bank for a channel.
+    ///
+    /// This is the same as sending the tuning bank select (RPN 4) and tuning program change (RPN 3)
+    /// messages, except that the tuning has to exist.
+    pub fn select_tuning(&mut self, chan: u8, bank: u8, program: u8) -> Result<(), OxiError> {
+        let channel = self.core.channels.get_mut(chan as usize)?;
+        let entry = self
+            .core
+            .tunings
+            .get(bank, program)
+            .ok_or(OxiError::TuningNotFound { bank, program })?;
+
+        channel.set_tuning_bank(bank);
+        channel.select_tuning(bank, program, Some(entry.tuning));
+        Ok(())
+    }
+
+    /// Same as [`Self::select_tuning`], but the notes that are playing on the channel
+    /// are retuned right away.
+    pub fn select_tuning_applied(
+        &mut self,
+        chan: u8,
+        bank: u8,
+        program: u8,
+    ) -> Result<(), OxiError> {
+        self.select_tuning(chan, bank, program)?;
+        self.retune_channel(chan);
+        Ok(())
+    }
+
+    fn retune_channel(&mut self, chan: u8) {
+        if let Ok(channel) = self.core.channels.get(chan as usize) {
+            self.core
+                .voices
+                .modulate_voices(channel, ModulateCtrl::Tuning);
+        }
+    }
+
+    /// Get a tuning of the tuning bank.
+    pub fn tuning(&self, bank: u8, program: u8) -> Option<&Tuning> {
+        self.core.tunings.get(bank, program).map(|e| &e.tuning)
+    }
+
+    /// Get the name of a tuning of the tuning bank.
+    pub fn tuning_name(&self, bank: u8, program: u8) -> Option<&str> {
+        self.core
+            .tunings
+            .get(bank, program)
+            .map(|e| e.name.as_str())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Tuning {
+    pub(crate) pitch: [f64; 128],
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tuning {
+    pub fn new() -> Self {
+        Self {
+            pitch: std::array::from_fn(|i| i as f64 * 100.0),
+        }
+    }
+
+    /// Create a new key-based tuning with given pitches.
+    /// The array should contain the pitch of every key in cents.
+    pub fn new_key_tuning(pitch: [f64; 128]) -> Self {
+        Self { pitch }
+    }
+
+    /// Create a new octave-based tuning with given pitches.
+    /// The array should contains derivation in cents from the well-tempered scale.
+    ///
+    /// For example, if pitches[0] equals -33, then the C-keys will be tuned 33 cents
+    /// below the well-tempered C.
+    pub fn new_octave_tuning(pitch: &[f64; 12]) -> Self {
+        let mut tuning = Self::new();
+
+        for (i, v) in tuning.pitch.iter_mut().enumerate() {
+            *v = i as f64 * 100.0 + pitch[i % 12];
+        }
+
+        tuning
+    }
+
+    pub fn as_slice(&self) -> &[f64; 128] {
+        &self.pitch
+    }
+
+    pub fn as_slice_mut(&mut self) -> &mut [f64; 128] {
+        &mut self.pitch
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Tuning;
+    use crate::{MidiEvent, Synth, SynthDescriptor};
+
+    fn rpn(synth: &mut Synth, channel: u8, lsb: u8, value: u8) {
+        for (ctrl, value) in [(101, 0), (100, lsb), (6, value)] {
+            synth
+                .send_event(MidiEvent::ControlChange {
+                    channel,
+                    ctrl,
+                    value,
+                })
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn tuning_bank() {
+        let mut synth = Synth::new(SynthDescriptor::default()).unwrap();
+
+        synth.define_tuning(0, 1, "Flat", Tuning::new_octave_tuning(&[-10.0; 12]));
+        synth.define_tuning(2, 0, "Sharp", Tuning::new_octave_tuning(&[10.0; 12]));
+
+        let list: Vec<_> = synth.tunings().collect();
+        assert_eq!(list, [(0, 1, "Flat"), (2, 0, "Sharp")]);
+
+        // Tuning bank select, then tuning program change
+        rpn(&mut synth, 0, 4, 2);
+        rpn(&mut synth, 0, 3, 0);
+        let pitch = synth.core.channels[0].tuning().unwrap().pitch[60];
+        assert_eq!(pitch, 6010.0);
+
+        // Redefining the tuning updates the channel
+        synth.define_tuning(2, 0, "Sharper", Tuning::new_octave_tuning(&[20.0; 12]));
+        let pitch = synth.core.channels[0].tuning().unwrap().pitch[60];
+        assert_eq!(pitch, 6020.0);
+
+        assert!(synth.select_tuning(1, 5, 5).is_err());
+        synth.select_tuning(1, 0, 1).unwrap();
+        let pitch = synth.core.channels[1].tuning().unwrap().pitch[60];
+        assert_eq!(pitch, 5990.0);
+
+        assert!(synth.remove_tuning(0, 1).is_some());
+        assert_eq!(synth.tunings().count(), 1);
+    }
+}
@@ -0,0 +1,440 @@
+//! Scala scale (`.scl`) and keyboard mapping (`.kbm`) files
+//!
+//! See <https://www.huygens-fokker.org/scala/scl_format.html> and
+//! <https://www.huygens-fokker.org/scala/help.htm#mappings>
+
+use super::Tuning;
+use crate::ScalaError;
+
+fn ratio_to_cents(ratio: f64) -> f64 {
+    1200.0 * ratio.log2()
+}
+
+/// Absolute pitch in cents of a frequency, the unit used by [`Tuning`]
+fn freq_to_cents(freq: f64) -> f64 {
+    6900.0 + ratio_to_cents(freq / 440.0)
+}
+
+/// Lines of a Scala file, skipping the `!` comments
+struct Lines<'a> {
+    lines: std::iter::Enumerate<std::str::Lines<'a>>,
+    line: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn new(src: &'a str) -> Self {
+        Self {
+            lines: src.lines().enumerate(),
+            line: 0,
+        }
+    }
+
+    fn next_line(&mut self) -> Result<(usize, &'a str), ScalaError> {
+        for (id, line) in self.lines.by_ref() {
+            self.line = id + 1;
+            if !line.starts_with('!') {
+                return Ok((self.line, line.trim()));
+            }
+        }
+
+        Err(ScalaError::UnexpectedEnd {
+            line: self.line + 1,
+        })
+    }
+
+    /// First token of the next non-empty line, anything after it is a comment
+    fn next_value(&mut self) -> Result<(usize, &'a str), ScalaError> {
+        loop {
+            let (line, text) = self.next_line()?;
+            if let Some(value) = text.split_whitespace().next() {
+                return Ok((line, value));
+            }
+        }
+    }
+
+    fn next_number<T: std::str::FromStr>(&mut self) -> Result<(usize, T), ScalaError> {
+        let (line, value) = self.next_value()?;
+        let number = value.parse().map_err(|_| ScalaError::InvalidNumber {
+            line,
+            value: value.to_owned(),
+        })?;
+        Ok((line, number))
+    }
+
+    fn next_key(&mut self) -> Result<(usize, u8), ScalaError> {
+        let (line, key) = self.next_number::<u8>()?;
+        if key > 127 {
+            return Err(ScalaError::InvalidNumber {
+                line,
+                value: key.to_string(),
+            });
+        }
+        Ok((line, key))
+    }
+}
+
+/// A scale, as stored in a Scala `.scl` file
+///
+/// The scale degrees are stored in cents above the 1/1 of the scale, which is implied.
+/// The last degree is the period of the scale, usually an octave.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scale {
+    description: String,
+    cents: Vec<f64>,
+}
+
+impl Scale {
+    /// Create a scale from the cents of the degrees above the 1/1, the last one is the period.
+    pub fn from_cents(description: &str, cents: Vec<f64>) -> Self {
+        Self {
+            description: description.to_owned(),
+            cents,
+        }
+    }
+
+    /// Create a scale that divides the octave in `n` equal steps.
+    pub fn edo(n: u16) -> Self {
+        let cents = (1..=n).map(|i| 1200.0 * i as f64 / n as f64).collect();
+        Self::from_cents(&format!("{n}-EDO"), cents)
+    }
+
+    /// Create a just intonation scale from the ratios of the degrees above the 1/1,
+    /// the last one is the period, eg. `(2, 1)`.
+    pub fn just(ratios: &[(u32, u32)]) -> Self {
+        let cents = ratios
+            .iter()
+            .map(|(n, d)| ratio_to_cents(*n as f64 / *d as f64))
+            .collect();
+        Self::from_cents("", cents)
+    }
+
+    /// Parse the content of a `.scl` file.
+    pub fn parse(scl: &str) -> Result<Self, ScalaError> {
+        let mut lines = Lines::new(scl);
+
+        let (_, description) = lines.next_line()?;
+        let (_, count) = lines.next_number::<usize>()?;
+
+        let cents = (0..count)
+            .map(|_| {
+                let (line, value) = lines.next_value()?;
+                parse_pitch(value).ok_or_else(|| ScalaError::InvalidPitch {
+                    line,
+                    value: value.to_owned(),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self::from_cents(description, cents))
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Number of degrees, the 1/1 excluded and the period included
+    pub fn len(&self) -> usize {
+        self.cents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cents.is_empty()
+    }
+
+    /// Cents of a scale degree above the 1/1, degrees past the period wrap to the next periods.
+    pub fn degree_cents(&self, degree: i32) -> f64 {
+        let Some(period) = self.cents.last() else {
+            return 0.0;
+        };
+
+        let len = self.cents.len() as i32;
+        let (period_id, degree) = (degree.div_euclid(len), degree.rem_euclid(len));
+
+        let cents = if degree == 0 {
+            0.0
+        } else {
+            self.cents[degree as usize - 1]
+        };
+
+        period_id as f64 * period + cents
+    }
+}
+
+/// Cents if the value contains a period, a ratio or an integer otherwise
+fn parse_pitch(value: &str) -> Option<f64> {
+    if value.contains('.') {
+        value.parse::<f64>().ok().filter(|c| c.is_finite())
+    } else {
+        let (n, d) = value.split_once('/').unwrap_or((value, "1"));
+        let (n, d) = (n.parse::<u64>().ok()?, d.parse::<u64>().ok()?);
+
+        if n == 0 || d == 0 {
+            None
+        } else {
+            Some(ratio_to_cents(n as f64 / d as f64))
+        }
+    }
+}
+
+/// Mapping of the MIDI keys to the degrees of a [`Scale`], as stored in a Scala `.kbm` file
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyboardMapping {
+    first_key: u8,
+    last_key: u8,
+    /// Key of the 1/1 of the scale
+    middle_key: u8,
+    reference_key: u8,
+    reference_freq: f64,
+    /// Scale degree of the formal octave of the mapping
+    octave_degree: i32,
+    /// Scale degree of each key of the pattern, `None` if the key is not mapped.
+    /// Empty for a linear mapping.
+    mapping: Vec<Option<i32>>,
+}
+
+impl KeyboardMapping {
+    /// Linear mapping of every key, with the 1/1 of the scale on `reference_key`
+    /// tuned to `reference_freq` Hz.
+    pub fn new(reference_key: u8, reference_freq: f64) -> Self {
+        Self {
+            first_key: 0,
+            last_key: 127,
+            middle_key: reference_key,
+            reference_key,
+            reference_freq,
+            octave_degree: 0,
+            mapping: Vec::new(),
+        }
+    }
+
+    /// Parse the content of a `.kbm` file.
+    pub fn parse(kbm: &str) -> Result<Self, ScalaError> {
+        let mut lines = Lines::new(kbm);
+
+        let (_, size) = lines.next_number::<usize>()?;
+        let (_, first_key) = lines.next_key()?;
+        let (_, last_key) = lines.next_key()?;
+        let (_, middle_key) = lines.next_key()?;
+        let (reference_line, reference_key) = lines.next_key()?;
+        let (_, reference_freq) = lines.next_number::<f64>()?;
+        let (_, octave_degree) = lines.next_number::<i32>()?;
+
+        let mut mapping = Vec::with_capacity(size);
+        for _ in 0..size {
+            let (line, value) = match lines.next_value() {
+                Ok(v) => v,
+                // Missing entries are not mapped
+                Err(ScalaError::UnexpectedEnd { .. }) => break,
+                Err(err) => return Err(err),
+            };
+
+            if value == "x" {
+                mapping.push(None);
+            } else {
+                let degree = value.parse().map_err(|_| ScalaError::InvalidNumber {
+                    line,
+                    value: value.to_owned(),
+                })?;
+                mapping.push(Some(degree));
+            }
+        }
+        mapping.resize(size, None);
+
+        let out = Self {
+            first_key,
+            last_key,
+            middle_key,
+            reference_key,
+            reference_freq,
+            octave_degree,
+            mapping,
+        };
+
+        if out.degree(reference_key).is_none() {
+            return Err(ScalaError::UnmappedReferenceKey {
+                line: reference_line,
+            });
+        }
+
+        Ok(out)
+    }
+
+    /// Scale degree of a key, `None` if the key is not mapped
+    fn degree(&self, key: u8) -> Option<i32> {
+        let offset = key as i32 - self.middle_key as i32;
+
+        if self.mapping.is_empty() {
+            return Some(offset);
+        }
+
+        let size = self.mapping.len() as i32;
+        let degree = self.mapping[offset.rem_euclid(size) as usize]?;
+
+        Some(degree + offset.div_euclid(size) * self.octave_degree)
+    }
+}
+
+impl Tuning {
+    /// Create a key-based tuning from a scale and a keyboard mapping.
+    ///
+    /// Keys that are not mapped keep their equal tempered pitch.
+    pub fn from_scale(scale: &Scale, mapping: &KeyboardMapping) -> Self {
+        let mut tuning = Self::new();
+
+        // The reference key is always mapped, this is checked when parsing the mapping
+        let reference = scale.degree_cents(mapping.degree(mapping.reference_key).unwrap_or(0));
+        let reference_cents = freq_to_cents(mapping.reference_freq);
+
+        for key in mapping.first_key..=mapping.last_key.min(127) {
+            if let Some(degree) = mapping.degree(key) {
+                tuning.pitch[key as usize] =
+                    reference_cents + scale.degree_cents(degree) - reference;
+            }
+        }
+
+        tuning
+    }
+
+    /// Create a key-based tuning from the content of a `.scl` file, and an optional `.kbm` file.
+    ///
+    /// Without a keyboard mapping, the 1/1 of the scale is mapped to middle C (key 60)
+    /// at 261.6256 Hz, like Scala does.
+    pub fn from_scala(scl: &str, kbm: Option<&str>) -> Result<Self, ScalaError> {
+        let scale = Scale::parse(scl)?;
+        let mapping = match kbm {
+            Some(kbm) => KeyboardMapping::parse(kbm)?,
+            None => KeyboardMapping::new(60, 440.0 * 2f64.powf(-9.0 / 12.0)),
+        };
+
+        Ok(Self::from_scale(&scale, &mapping))
+    }
+
+    /// Create a tuning that divides the octave in `n` equal steps,
+    /// `reference_key` is tuned to `reference_freq` Hz.
+    pub fn new_edo(n: u16, reference_key: u8, reference_freq: f64) -> Self {
+        Self::from_scale(
+            &Scale::edo(n),
+            &KeyboardMapping::new(reference_key, reference_freq),
+        )
+    }
+
+    /// Create a just intonation tuning from the ratios of the scale degrees, see [`Scale::just`],
+    /// `reference_key` is the 1/1 of the scale and is tuned to `reference_freq` Hz.
+    pub fn new_just(ratios: &[(u32, u32)], reference_key: u8, reference_freq: f64) -> Self {
+        Self::from_scale(
+            &Scale::just(ratios),
+            &KeyboardMapping::new(reference_key, reference_freq),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{KeyboardMapping, Scale};
+    use crate::{ScalaError, Tuning};
+
+    const MEANTONE: &str = "! meanquar.scl
+!
+1/4-comma meantone scale. Pietro Aaron's temperament (1523)
+ 12
+!
+ 76.04900
+ 193.15686
+ 310.26471
+ 5/4
+ 503.42157
+ 579.47057
+ 696.57843
+ 25/16
+ 889.73529
+ 1006.84314
+ 1082.89214
+ 2/1
+";
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+
+    #[test]
+    fn scl() {
+        let scale = Scale::parse(MEANTONE).unwrap();
+        assert_eq!(
+            scale.description(),
+            "1/4-comma meantone scale. Pietro Aaron's temperament (1523)"
+        );
+        assert_eq!(scale.len(), 12);
+        assert_close(scale.degree_cents(4), 386.3137138648348);
+        assert_close(scale.degree_cents(-12), -1200.0);
+
+        // 12-EDO on the Scala default mapping is the default tuning
+        let tuning = Tuning::from_scala(
+            "12-EDO\n12\n100.\n200.\n300.\n400.\n500.\n600.\n700.\n800.\n900.\n1000.\n1100.\n2/1",
+            None,
+        )
+        .unwrap();
+        for (a, b) in tuning.as_slice().iter().zip(Tuning::new().as_slice()) {
+            assert_close(*a, *b);
+        }
+
+        let err = Scale::parse("desc\n3\n100.0\n3/0\n2/1").unwrap_err();
+        assert_eq!(
+            err,
+            ScalaError::InvalidPitch {
+                line: 4,
+                value: "3/0".into()
+            }
+        );
+        assert_eq!(
+            Scale::parse("desc\n3\n100.0\n").unwrap_err(),
+            ScalaError::UnexpectedEnd { line: 4 }
+        );
+    }
+
+    #[test]
+    fn kbm() {
+        // White keys only, A4 = 432 Hz
+        let kbm = "! white keys
+12
+0
+127
+60
+69
+432.0
+7
+0
+x
+1
+x
+2
+3
+x
+4
+x
+5
+x
+6
+";
+        let mapping = KeyboardMapping::parse(kbm).unwrap();
+        let tuning = Tuning::from_scale(&Scale::edo(7), &mapping);
+
+        assert_close(
+            tuning.as_slice()[69],
+            6900.0 + 1200.0 * (432f64 / 440.0).log2(),
+        );
+        assert_close(tuning.as_slice()[71] - tuning.as_slice()[69], 1200.0 / 7.0);
+        assert_close(tuning.as_slice()[72] - tuning.as_slice()[60], 1200.0);
+        // Unmapped keys are left alone
+        assert_eq!(tuning.as_slice()[61], 6100.0);
+
+        let err = KeyboardMapping::parse(&kbm.replace("\n69\n", "\n70\n")).unwrap_err();
+        assert_eq!(err, ScalaError::UnmappedReferenceKey { line: 6 });
+
+        let tuning = Tuning::new_edo(19, 69, 440.0);
+        assert_close(tuning.as_slice()[70], 6900.0 + 1200.0 / 19.0);
+
+        let tuning = Tuning::new_just(&[(9, 8), (5, 4), (2, 1)], 60, 261.6255653005986);
+        assert_close(tuning.as_slice()[62], 6000.0 + 1200.0 * (5f64 / 4.0).log2());
+        assert_close(tuning.as_slice()[63], 7200.0);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::Synth;
+use crate::{OxiError, Synth, TimedEvent};
 
 /// The trait which implements samples data buffer interface
 pub trait IsSamples {
@@ -70,6 +70,52 @@ impl IsSamples for &mut [f64] {
     }
 }
 
+/// A non-interleaved stereo buffer
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StereoBuffer {
+    pub left: Vec<f32>,
+    pub right: Vec<f32>,
+}
+
+impl StereoBuffer {
+    pub(crate) fn new(len: usize) -> Self {
+        Self {
+            left: vec![0.0; len],
+            right: vec![0.0; len],
+        }
+    }
+
+    pub(crate) fn mix(&mut self, other: &StereoBuffer) {
+        self.left
+            .iter_mut()
+            .zip(other.left.iter())
+            .chain(self.right.iter_mut().zip(other.right.iter()))
+            .for_each(|(out, s)| *out += s);
+    }
+
+    /// Samples interleaved, left first
+    pub fn interleaved(&self) -> Vec<f32> {
+        self.left
+            .iter()
+            .zip(self.right.iter())
+            .flat_map(|(l, r)| [*l, *r])
+            .collect()
+    }
+}
+
+/// Stems rendered by [`Synth::render_stems()`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Stems {
+    /// Dry output of every MIDI channel, indexed by channel
+    pub channels: Vec<StereoBuffer>,
+    /// Sum of the channels
+    pub dry: StereoBuffer,
+    /// Reverb return
+    pub reverb: StereoBuffer,
+    /// Chorus return
+    pub chorus: StereoBuffer,
+}
+
 /// Write synth output to a buffer
 impl Synth {
     /// Write sound samples to the sample data buffer
@@ -78,6 +124,69 @@ impl Synth {
         samples.write_samples(self)
     }
 
+    /// Write sound samples to the sample data buffer, with events that take effect at a frame
+    /// of the buffer.
+    ///
+    /// The notes start on their exact frame. The other events, note-offs, controller changes and
+    /// pitch bends included, take effect at the start of the block that contains their frame,
+    /// see [`SynthDescriptor::block_size`](crate::SynthDescriptor::block_size). A smaller block
+    /// size gives them a finer timing.
+    /// Events after the end of the buffer are kept for the next writes.
+    ///
+    /// The frames left over in the current block by a previous write are already rendered,
+    /// events on those frames take effect at the start of the next block. This does not happen
+    /// when every write is a multiple of the block size long.
+    ///
+    /// Returns an error, before anything is written, if one of the events is invalid.
+    pub fn render<S: IsSamples>(
+        &mut self,
+        samples: S,
+        events: &[TimedEvent],
+    ) -> Result<(), OxiError> {
+        for event in events {
+            event.event.check()?;
+        }
+        for event in events {
+            self.core.queue_event(event.frame, event.event);
+        }
+
+        samples.write_samples(self);
+        Ok(())
+    }
+
+    /// Write every audio group to its own non-interleaved stereo buffer pair, for multi-output
+    /// hosts.
+    ///
+    /// `groups[i]` receives the audio group `i`. The notes of the MIDI channel `n` are in the
+    /// group `n % audio_groups`, see [`SynthDescriptor::audio_groups`](crate::SynthDescriptor::audio_groups),
+    /// unless the channel was routed with [`Synth::set_channel_output()`].
+    /// The effects returns are not mixed into the groups, they are written to `fx`:
+    /// `fx[0]` is the reverb and `fx[1]` the chorus.
+    ///
+    /// The length of the shortest buffer is written. The buffers of groups that do not exist
+    /// are filled with silence.
+    ///
+    /// It can be mixed with [`Synth::write()`], the rest of a partially read block is
+    /// converted to the layout of the function that reads it.
+    pub fn write_multi(
+        &mut self,
+        groups: &mut [(&mut [f32], &mut [f32])],
+        fx: &mut [(&mut [f32], &mut [f32])],
+    ) {
+        self.core.write_multi(groups, fx);
+    }
+
+    /// Render `len` frames of every MIDI channel to its own stereo stem, in one pass.
+    ///
+    /// The channel stems are dry, the effects returns are rendered to their own buses.
+    /// Mixing `dry`, `reverb` and `chorus` gives the output of [`Synth::write()`].
+    ///
+    /// The rest of a block that was partially read by another write function has no channel
+    /// stems, it is only part of `dry`, `reverb` and `chorus`.
+    pub fn render_stems(&mut self, len: usize) -> Stems {
+        self.core.render_stems(len)
+    }
+
     #[inline(always)]
     pub fn read_next(&mut self) -> (f32, f32) {
         self.core.read_next()
@@ -155,3 +264,255 @@ impl Synth {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::test_util::{cc, playing_synth, send, sin_synth};
+    use crate::{MidiEvent, Synth, SynthDescriptor, TimedEvent};
+
+    fn first_sound(synth: &mut Synth, len: usize, events: &[TimedEvent]) -> Option<usize> {
+        let mut left = vec![0.0; len];
+        let mut right = vec![0.0; len];
+        synth
+            .render((&mut left[..], &mut right[..]), events)
+            .unwrap();
+        left.iter().position(|s| *s != 0.0)
+    }
+
+    #[test]
+    fn render_timed_events() {
+        let new_synth = |block_size| {
+            sin_synth(SynthDescriptor {
+                reverb_active: false,
+                chorus_active: false,
+                block_size,
+                ..Default::default()
+            })
+        };
+        let note_on = |frame| TimedEvent {
+            frame,
+            event: MidiEvent::NoteOn {
+                channel: 0,
+                key: 60,
+                vel: 100,
+            },
+        };
+
+        // Within the second block
+        let mut synth = new_synth(64);
+        let first = first_sound(&mut synth, 256, &[note_on(100)]).unwrap();
+        assert!((100..=101).contains(&first));
+
+        // After the end of the buffer
+        let mut synth = new_synth(64);
+        assert_eq!(first_sound(&mut synth, 128, &[note_on(200)]), None);
+        let first = first_sound(&mut synth, 128, &[]).unwrap();
+        assert!((72..=73).contains(&first));
+
+        // After a write that is not a multiple of the block size
+        let mut synth = new_synth(64);
+        assert_eq!(first_sound(&mut synth, 10, &[]), None);
+        let first = first_sound(&mut synth, 128, &[note_on(77)]).unwrap();
+        assert!((77..=78).contains(&first));
+
+        let invalid = TimedEvent {
+            frame: 0,
+            event: MidiEvent::NoteOn {
+                channel: 0,
+                key: 128,
+                vel: 100,
+            },
+        };
+        assert!(synth.render(&mut [0.0; 128][..], &[invalid]).is_err());
+
+        // Any block size, with small blocks the 1ms default delay of the volume envelope
+        // is not rounded down to 0 blocks
+        let mut synth = new_synth(1000);
+        let first = first_sound(&mut synth, 4000, &[note_on(1500)]).unwrap();
+        assert!((1500..=1501).contains(&first));
+
+        // The note starts on frame 1500, the volume envelope delay then lasts 2 blocks
+        let mut synth = new_synth(16);
+        let first = first_sound(&mut synth, 4000, &[note_on(1500)]).unwrap();
+        assert!((1500..=1501).contains(&(first - 2 * 16)));
+    }
+
+    #[test]
+    fn channel_output() {
+        let mut synth = sin_synth(SynthDescriptor {
+            audio_groups: 2,
+            ..Default::default()
+        });
+
+        assert_eq!(synth.channel_output(0).unwrap(), 0);
+        synth.set_channel_output(0, 1).unwrap();
+        synth.set_channel_fx_buses(0, false, false).unwrap();
+        assert_eq!(synth.channel_output(0).unwrap(), 1);
+        assert_eq!(synth.channel_fx_buses(0).unwrap(), (false, false));
+        assert!(synth.set_channel_output(0, 2).is_err());
+
+        synth
+            .send_event(MidiEvent::NoteOn {
+                channel: 0,
+                key: 60,
+                vel: 100,
+            })
+            .unwrap();
+
+        let mut bufs = vec![vec![0.0; 1000]; 8];
+        let [g0l, g0r, g1l, g1r, rl, rr, cl, cr] = &mut bufs[..] else {
+            unreachable!()
+        };
+        synth.write_multi(&mut [(g0l, g0r), (g1l, g1r)], &mut [(rl, rr), (cl, cr)]);
+
+        let silent = |buf: &[f32]| buf.iter().all(|s| *s == 0.0);
+        assert!(silent(&bufs[0]) && silent(&bufs[1]));
+        assert!(!silent(&bufs[2]) && !silent(&bufs[3]));
+        // The reverb keeps a tiny DC offset, to avoid denormals
+        assert!(bufs[4..].iter().flatten().all(|s| s.abs() < 1e-6));
+    }
+
+    #[test]
+    fn render_stems() {
+        let new_synth = || {
+            let mut synth = playing_synth();
+            let note_on = MidiEvent::NoteOn {
+                channel: 3,
+                key: 67,
+                vel: 100,
+            };
+            send(&mut synth, note_on);
+            synth
+        };
+
+        let mut synth = new_synth();
+        let stems = synth.render_stems(1000);
+
+        let silent = |buf: &crate::StereoBuffer| buf.left.iter().all(|s| *s == 0.0);
+        assert_eq!(stems.channels.len(), 16);
+        for (id, stem) in stems.channels.iter().enumerate() {
+            assert_eq!(silent(stem), id != 0 && id != 3);
+        }
+        assert!(!silent(&stems.reverb));
+        assert_eq!(stems.dry.left.len(), 1000);
+
+        // The stems mix back to the regular output
+        let mut mixed = stems.dry.clone();
+        mixed.mix(&stems.reverb);
+        mixed.mix(&stems.chorus);
+
+        let mut synth = new_synth();
+        let mut left = vec![0.0; 1000];
+        let mut right = vec![0.0; 1000];
+        synth.write((&mut left[..], &mut right[..]));
+
+        for (a, b) in mixed.left.iter().zip(left.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+        for (a, b) in mixed.right.iter().zip(right.iter()) {
+            assert!((a - b).abs() < 1e-5);
+        }
+
+        // Renders that end within a block give the same stems
+        let mut synth = new_synth();
+        let first = synth.render_stems(100);
+        let second = synth.render_stems(900);
+        for (id, stem) in stems.channels.iter().enumerate() {
+            assert_eq!(stem.left[..100], first.channels[id].left[..]);
+            assert_eq!(stem.left[100..], second.channels[id].left[..]);
+            assert_eq!(stem.right[100..], second.channels[id].right[..]);
+        }
+        assert_eq!(stems.reverb.left[100..], second.reverb.left[..]);
+    }
+
+    #[test]
+    fn write_multi() {
+        let mut synth = sin_synth(SynthDescriptor {
+            audio_groups: 2,
+            ..Default::default()
+        });
+
+        synth
+            .send_event(MidiEvent::NoteOn {
+                channel: 1,
+                key: 60,
+                vel: 100,
+            })
+            .unwrap();
+
+        let mut bufs = vec![vec![0.0; 1000]; 8];
+        let [g0l, g0r, g1l, g1r, g2l, g2r, rl, rr] = &mut bufs[..] else {
+            unreachable!()
+        };
+        synth.write_multi(&mut [(g0l, g0r), (g1l, g1r), (g2l, g2r)], &mut [(rl, rr)]);
+
+        let silent = |buf: &[f32]| buf.iter().all(|s| *s == 0.0);
+        assert!(silent(&bufs[0]) && silent(&bufs[1]));
+        assert!(!silent(&bufs[2]) && !silent(&bufs[3]));
+        // There are only 2 groups
+        assert!(silent(&bufs[4]) && silent(&bufs[5]));
+        assert!(!silent(&bufs[6]) && !silent(&bufs[7]));
+    }
+
+    #[test]
+    fn switch_write_functions() {
+        fn write(synth: &mut Synth, len: usize) -> [Vec<f32>; 2] {
+            let (mut left, mut right) = (vec![0.0; len], vec![0.0; len]);
+            synth.write((&mut left[..], &mut right[..]));
+            [left, right]
+        }
+        // Group and effects returns summed back together
+        fn write_multi(synth: &mut Synth, len: usize) -> [Vec<f32>; 2] {
+            let mut bufs = vec![vec![0.0; len]; 6];
+            let [gl, gr, rl, rr, cl, cr] = &mut bufs[..] else {
+                unreachable!()
+            };
+            synth.write_multi(&mut [(gl, gr)], &mut [(rl, rr), (cl, cr)]);
+            [0, 1].map(|side| {
+                (0..len)
+                    .map(|i| bufs[side][i] + bufs[2 + side][i] + bufs[4 + side][i])
+                    .collect()
+            })
+        }
+        fn assert_close(a: &[f32], b: &[f32]) {
+            assert_eq!(a.len(), b.len());
+            for (a, b) in a.iter().zip(b) {
+                assert!((a - b).abs() < 1e-5, "{a} != {b}");
+            }
+        }
+
+        // Both effects are used, so that their returns are part of the output
+        fn new_synth() -> Synth {
+            let mut synth = playing_synth();
+            send(&mut synth, cc(91, 127));
+            send(&mut synth, cc(93, 127));
+            synth
+        }
+
+        let reference = write(&mut new_synth(), 1000);
+
+        // Both switches happen within a block
+        let mut synth = new_synth();
+        let head = write(&mut synth, 100);
+        let middle = write_multi(&mut synth, 500);
+        let tail = write(&mut synth, 400);
+        for side in 0..2 {
+            assert_close(&head[side], &reference[side][..100]);
+            assert_close(&middle[side], &reference[side][100..600]);
+            assert_close(&tail[side], &reference[side][600..]);
+        }
+
+        let mut synth = new_synth();
+        let head = write(&mut synth, 100);
+        let mut stems = synth.render_stems(500);
+        stems.dry.mix(&stems.reverb);
+        stems.dry.mix(&stems.chorus);
+        let tail = write(&mut synth, 400);
+        let middle = [stems.dry.left, stems.dry.right];
+        for side in 0..2 {
+            assert_close(&head[side], &reference[side][..100]);
+            assert_close(&middle[side], &reference[side][100..600]);
+            assert_close(&tail[side], &reference[side][600..]);
+        }
+    }
+}
@@ -0,0 +1,376 @@
+//! Render a MIDI file with a SoundFont to a WAV or raw PCM file
+//!
+//! Run `oxisynth --help` for the options.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use oxisynth::smf::{MidiFile, MidiPlayer};
+use oxisynth::wav::{PcmWriter, SampleFormat, WavWriter};
+use oxisynth::{
+    ChorusParams, InterpolationMethod, ReverbParams, SoundFont, Synth, SynthDescriptor,
+};
+use oxisynth_chorus::ChorusMode;
+
+const USAGE: &str = "\
+Usage: oxisynth [OPTIONS] <SOUNDFONT> <MIDI>
+
+Render a MIDI file with a SoundFont (.sf2, or .sf3 with the `sf3` feature) to an audio file.
+
+Options:
+  -o, --output <FILE>           Output file, `-` for stdout with --raw [default: MIDI file with .wav]
+  -f, --format <FORMAT>         Sample format: s16, s24 or f32 [default: s16]
+      --raw                     Write headerless little-endian PCM instead of WAV
+  -r, --sample-rate <HZ>        Sample rate, 8000 to 96000 [default: 44100]
+  -g, --gain <GAIN>             Master gain, 0 to 10 [default: 0.2]
+  -p, --polyphony <VOICES>      Maximum number of voices [default: 256]
+  -i, --interpolation <METHOD>  Interpolation: none, linear, 4th or 7th [default: 4th]
+      --tail <SECONDS>          Time rendered after the end of the song [default: 2]
+      --no-reverb               Disable the reverb
+      --reverb-room-size <SIZE> [default: 0.2]
+      --reverb-damp <DAMP>      [default: 0]
+      --reverb-width <WIDTH>    [default: 0.5]
+      --reverb-level <LEVEL>    [default: 0.9]
+      --no-chorus               Disable the chorus
+      --chorus-nr <N>           Number of chorus voices [default: 3]
+      --chorus-level <LEVEL>    [default: 2]
+      --chorus-speed <HZ>       [default: 0.3]
+      --chorus-depth <MS>       [default: 8]
+      --chorus-mode <MODE>      sine or triangle [default: sine]
+  -h, --help                    Print this help
+";
+
+/// Frames rendered at once
+const CHUNK_FRAMES: usize = 4096;
+
+struct Args {
+    soundfont: PathBuf,
+    midi: PathBuf,
+    output: PathBuf,
+    format: SampleFormat,
+    raw: bool,
+    tail: f32,
+    desc: SynthDescriptor,
+    reverb: ReverbParams,
+    chorus: ChorusParams,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut positional = Vec::new();
+    let mut output = None;
+    let mut format = SampleFormat::I16;
+    let mut raw = false;
+    let mut tail: f32 = 2.0;
+    let mut desc = SynthDescriptor::default();
+    let mut reverb = ReverbParams::default();
+    let mut chorus = ChorusParams::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {arg}"))
+        };
+        fn number<T: std::str::FromStr>(arg: &str, value: String) -> Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("Invalid value for {arg}: {value}"))
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-o" | "--output" => output = Some(PathBuf::from(value()?)),
+            "-f" | "--format" => {
+                format = match value()?.as_str() {
+                    "s16" => SampleFormat::I16,
+                    "s24" => SampleFormat::I24,
+                    "f32" => SampleFormat::F32,
+                    other => return Err(format!("Unknown sample format: {other}")),
+                }
+            }
+            "--raw" => raw = true,
+            "-r" | "--sample-rate" => desc.sample_rate = number(&arg, value()?)?,
+            "-g" | "--gain" => desc.gain = number(&arg, value()?)?,
+            "-p" | "--polyphony" => desc.polyphony = number(&arg, value()?)?,
+            "-i" | "--interpolation" => {
+                desc.interpolation = match value()?.as_str() {
+                    "none" => InterpolationMethod::None,
+                    "linear" => InterpolationMethod::Linear,
+                    "4th" => InterpolationMethod::FourthOrder,
+                    "7th" => InterpolationMethod::SeventhOrder,
+                    other => return Err(format!("Unknown interpolation: {other}")),
+                }
+            }
+            "--tail" => tail = number(&arg, value()?)?,
+            "--no-reverb" => desc.reverb_active = false,
+            "--reverb-room-size" => reverb.roomsize = number(&arg, value()?)?,
+            "--reverb-damp" => reverb.damp = number(&arg, value()?)?,
+            "--reverb-width" => reverb.width = number(&arg, value()?)?,
+            "--reverb-level" => reverb.level = number(&arg, value()?)?,
+            "--no-chorus" => desc.chorus_active = false,
+            "--chorus-nr" => chorus.nr = number(&arg, value()?)?,
+            "--chorus-level" => chorus.level = number(&arg, value()?)?,
+            "--chorus-speed" => chorus.speed = number(&arg, value()?)?,
+            "--chorus-depth" => chorus.depth = number(&arg, value()?)?,
+            "--chorus-mode" => {
+                chorus.mode = match value()?.as_str() {
+                    "sine" => ChorusMode::Sine,
+                    "triangle" => ChorusMode::Triangle,
+                    other => return Err(format!("Unknown chorus mode: {other}")),
+                }
+            }
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("Unknown option: {arg}"))
+            }
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+
+    let [soundfont, midi]: [PathBuf; 2] = positional
+        .try_into()
+        .map_err(|_| "Expected a SoundFont and a MIDI file".to_string())?;
+
+    if !tail.is_finite() || tail < 0.0 {
+        return Err(format!("Invalid tail length: {tail}"));
+    }
+
+    let output = output.unwrap_or_else(|| midi.with_extension(if raw { "pcm" } else { "wav" }));
+    if output.as_os_str() == "-" && !raw {
+        return Err("Writing to stdout requires --raw".to_string());
+    }
+
+    Ok(Some(Args {
+        soundfont,
+        midi,
+        output,
+        format,
+        raw,
+        tail,
+        desc,
+        reverb,
+        chorus,
+    }))
+}
+
+/// Destination of the rendered samples
+enum Output {
+    Wav(WavWriter<BufWriter<File>>),
+    Pcm(PcmWriter<Box<dyn Write>>),
+}
+
+impl Output {
+    fn write(&mut self, synth: &mut Synth, frames: usize) -> io::Result<()> {
+        match self {
+            Self::Wav(wav) => wav.write(synth, frames),
+            Self::Pcm(pcm) => pcm.write(synth, frames),
+        }
+    }
+
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Self::Wav(wav) => wav.finish()?.flush(),
+            Self::Pcm(pcm) => pcm.into_inner()?.flush(),
+        }
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let sample_rate = args.desc.sample_rate;
+    let tail = (args.tail * sample_rate).round() as usize;
+
+    let mut synth = Synth::new(args.desc).map_err(|err| err.to_string())?;
+    synth.set_reverb_params(&args.reverb);
+    synth.set_chorus_params(&args.chorus);
+
+    let mut file = File::open(&args.soundfont)
+        .map_err(|err| format!("{}: {err}", args.soundfont.display()))?;
+    let font =
+        SoundFont::load(&mut file).map_err(|err| format!("{}: {err}", args.soundfont.display()))?;
+    synth.add_font(font, true);
+
+    let mut file =
+        File::open(&args.midi).map_err(|err| format!("{}: {err}", args.midi.display()))?;
+    let midi =
+        MidiFile::load(&mut file).map_err(|err| format!("{}: {err}", args.midi.display()))?;
+    let mut player = MidiPlayer::new(&midi, &synth);
+
+    let output_path = args.output;
+    let create = |path: &PathBuf| {
+        File::create(path)
+            .map(BufWriter::new)
+            .map_err(|err| format!("{}: {err}", path.display()))
+    };
+
+    let mut output = if args.raw {
+        let writer: Box<dyn Write> = if output_path.as_os_str() == "-" {
+            Box::new(BufWriter::new(io::stdout().lock()))
+        } else {
+            Box::new(create(&output_path)?)
+        };
+        Output::Pcm(PcmWriter::new(writer, args.format))
+    } else {
+        let writer = create(&output_path)?;
+        Output::Wav(
+            WavWriter::new(writer, &synth, args.format)
+                .map_err(|err| format!("{}: {err}", output_path.display()))?,
+        )
+    };
+
+    let write_err = |err: io::Error| format!("{}: {err}", output_path.display());
+
+    // Stop at the end of the song, the tail is rendered after it
+    let end = (player.duration().as_secs_f64() * sample_rate as f64).round() as u64;
+    let mut frame = 0;
+
+    let mut res = Ok(());
+    while !player.is_finished() && res.is_ok() {
+        let len = (end.saturating_sub(frame) as usize).clamp(1, CHUNK_FRAMES);
+        player.render_with(&mut synth, len, |synth, range| {
+            if res.is_ok() {
+                res = output.write(synth, range.len());
+            }
+        });
+        frame += len as u64;
+    }
+    res.map_err(write_err)?;
+
+    output.write(&mut synth, tail).map_err(write_err)?;
+    output.finish().map_err(write_err)?;
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            print!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Args>, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn parse_ok(args: &[&str]) -> Args {
+        match parse(args) {
+            Ok(Some(args)) => args,
+            Ok(None) => panic!("help requested"),
+            Err(err) => panic!("{err}"),
+        }
+    }
+
+    fn parse_err(args: &[&str]) -> String {
+        match parse(args) {
+            Err(err) => err,
+            Ok(_) => panic!("{args:?} was accepted"),
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let args = parse_ok(&["font.sf2", "dir/song.mid"]);
+        assert_eq!(args.soundfont, PathBuf::from("font.sf2"));
+        assert_eq!(args.midi, PathBuf::from("dir/song.mid"));
+        assert_eq!(args.output, PathBuf::from("dir/song.wav"));
+        assert_eq!(args.format, SampleFormat::I16);
+        assert!(!args.raw);
+        assert_eq!(args.tail, 2.0);
+
+        let args = parse_ok(&["font.sf2", "dir/song.mid", "--raw"]);
+        assert_eq!(args.output, PathBuf::from("dir/song.pcm"));
+        assert!(args.raw);
+    }
+
+    #[test]
+    fn options() {
+        let args = parse_ok(&[
+            "-o",
+            "out.wav",
+            "font.sf2",
+            "-f",
+            "f32",
+            "--sample-rate",
+            "48000",
+            "-i",
+            "7th",
+            "--tail",
+            "0.5",
+            "--no-reverb",
+            "--chorus-mode",
+            "triangle",
+            "--chorus-nr",
+            "5",
+            "song.mid",
+        ]);
+        assert_eq!(args.output, PathBuf::from("out.wav"));
+        assert_eq!(args.format, SampleFormat::F32);
+        assert_eq!(args.desc.sample_rate, 48000.0);
+        assert_eq!(args.desc.interpolation, InterpolationMethod::SeventhOrder);
+        assert_eq!(args.tail, 0.5);
+        assert!(!args.desc.reverb_active);
+        assert!(args.desc.chorus_active);
+        assert!(matches!(args.chorus.mode, ChorusMode::Triangle));
+        assert_eq!(args.chorus.nr, 5);
+
+        let args = parse_ok(&["font.sf2", "song.mid", "--raw", "-o", "-"]);
+        assert_eq!(args.output, PathBuf::from("-"));
+
+        assert!(matches!(parse(&["font.sf2", "--help"]), Ok(None)));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse_err(&["font.sf2"]),
+            "Expected a SoundFont and a MIDI file"
+        );
+        assert_eq!(
+            parse_err(&["a.sf2", "b.mid", "c.mid"]),
+            "Expected a SoundFont and a MIDI file"
+        );
+        assert_eq!(
+            parse_err(&["font.sf2", "song.mid", "-o"]),
+            "Missing value for -o"
+        );
+        assert_eq!(
+            parse_err(&["font.sf2", "song.mid", "--loud"]),
+            "Unknown option: --loud"
+        );
+        assert_eq!(
+            parse_err(&["font.sf2", "song.mid", "-g", "loud"]),
+            "Invalid value for -g: loud"
+        );
+        assert_eq!(
+            parse_err(&["font.sf2", "song.mid", "-f", "s8"]),
+            "Unknown sample format: s8"
+        );
+        assert_eq!(
+            parse_err(&["font.sf2", "song.mid", "--tail", "-1"]),
+            "Invalid tail length: -1"
+        );
+        assert_eq!(
+            parse_err(&["font.sf2", "song.mid", "-o", "-"]),
+            "Writing to stdout requires --raw"
+        );
+    }
+}
@@ -4,7 +4,7 @@ use std::sync::Arc;
 use super::super::soundfont::{Preset, SoundFont};
 
 use crate::arena::Index;
-use crate::core::InterpolationMethod;
+use crate::core::{InterpolationMethod, NotePriority};
 use crate::midi_event::ControlFunction;
 use crate::GeneratorType;
 use crate::Tuning;
@@ -66,6 +66,28 @@ pub struct Channel {
 
     interp_method: InterpolationMethod,
     tuning: Option<Tuning>,
+    /// Bank and program of the tuning, if it was selected from the tuning bank
+    tuning_id: Option<(u8, u8)>,
+    /// Tuning bank used by the next tuning program change (RPN 3)
+    tuning_bank: u8,
+
+    /// Previous key played on the channel, the default source of portamento
+    prev_key: Option<u8>,
+    /// Source key of the next note, set with the portamento control (CC84)
+    portamento_control: Option<u8>,
+
+    /// Mono mode (CC126), cleared by poly mode (CC127)
+    mono: bool,
+    note_priority: NotePriority,
+    /// Keys that are down with their velocity, in the order they were pressed
+    held_keys: Vec<(u8, u8)>,
+
+    /// Audio group of the channel, `None` to wrap the channel number around the number of groups
+    audio_group: Option<usize>,
+    /// Whether the voices of the channel are sent to the reverb bus
+    reverb_send: bool,
+    /// Whether the voices of the channel are sent to the chorus bus
+    chorus_send: bool,
 
     nrpn_select: i16,
     nrpn_active: i16,
@@ -95,6 +117,19 @@ impl Channel {
 
             interp_method: InterpolationMethod::default(),
             tuning: None,
+            tuning_id: None,
+            tuning_bank: 0,
+
+            prev_key: None,
+            portamento_control: None,
+
+            mono: false,
+            note_priority: NotePriority::default(),
+            held_keys: Vec::new(),
+
+            audio_group: None,
+            reverb_send: true,
+            chorus_send: true,
 
             nrpn_select: 0,
             nrpn_active: 0,
@@ -106,6 +141,31 @@ impl Channel {
         chan
     }
 
+    /// Audio group of the channel, out of `audio_groups`
+    pub fn audio_group(&self, audio_groups: usize) -> usize {
+        // By default the channels are wrapped around the number of audio groups.
+        // For example with 2 groups, MIDI channels 1, 3, 5 etc go to the group 1, and
+        // channels 2, 4, 6 etc to the group 2.
+        self.audio_group.unwrap_or(self.id % audio_groups)
+    }
+
+    pub fn set_audio_group(&mut self, group: usize) {
+        self.audio_group = Some(group);
+    }
+
+    pub fn reverb_send(&self) -> bool {
+        self.reverb_send
+    }
+
+    pub fn chorus_send(&self) -> bool {
+        self.chorus_send
+    }
+
+    pub fn set_fx_sends(&mut self, reverb: bool, chorus: bool) {
+        self.reverb_send = reverb;
+        self.chorus_send = chorus;
+    }
+
     pub fn init(&mut self, preset: Option<Arc<Preset>>) {
         self.prognum = 0;
         self.banknum = 0;
@@ -114,6 +174,11 @@ impl Channel {
         self.preset = preset;
         self.interp_method = Default::default();
         self.tuning = None;
+        self.tuning_id = None;
+        self.tuning_bank = 0;
+        self.prev_key = None;
+        self.mono = false;
+        self.held_keys.clear();
         self.nrpn_select = 0;
         self.nrpn_active = 0;
     }
@@ -150,6 +215,7 @@ impl Channel {
         }
 
         self.key_pressure.fill(0);
+        self.portamento_control = None;
 
         self.cc[ControlFunction::RegisteredParameterNumberLsb] = 127;
         self.cc[ControlFunction::RegisteredParameterNumberMsb] = 127;
@@ -205,6 +271,9 @@ impl Channel {
     }
 
     pub fn set_preset(&mut self, preset: Option<Arc<Preset>>) {
+        if let Some(preset) = &preset {
+            preset.preload();
+        }
         self.preset = preset;
     }
 
@@ -270,6 +339,121 @@ impl Channel {
 
     pub fn set_tuning(&mut self, val: Option<Tuning>) {
         self.tuning = val;
+        self.tuning_id = None;
+    }
+
+    pub fn tuning_id(&self) -> Option<(u8, u8)> {
+        self.tuning_id
+    }
+
+    /// Use a tuning of the tuning bank, `None` if it is not defined (yet)
+    pub fn select_tuning(&mut self, bank: u8, program: u8, tuning: Option<Tuning>) {
+        self.tuning = tuning;
+        self.tuning_id = Some((bank, program));
+    }
+
+    pub fn tuning_bank(&self) -> u8 {
+        self.tuning_bank
+    }
+
+    pub fn set_tuning_bank(&mut self, bank: u8) {
+        self.tuning_bank = bank;
+    }
+
+    /// Portamento time in milliseconds (CC5 and CC37)
+    pub fn portamento_time(&self) -> u32 {
+        ((self.cc[ControlFunction::PortamentoTime] as u32) << 7)
+            | self.cc[ControlFunction::PortamentoTimeLsb] as u32
+    }
+
+    /// Portamento switch (CC65)
+    pub fn portamento(&self) -> bool {
+        self.cc[ControlFunction::PortamentoOnOff] >= 64
+    }
+
+    /// Soft pedal (CC67)
+    pub fn soft_pedal(&self) -> bool {
+        self.cc[ControlFunction::SoftPedal] >= 64
+    }
+
+    /// Hold2 pedal (CC69)
+    pub fn hold2(&self) -> bool {
+        self.cc[ControlFunction::Hold2] >= 64
+    }
+
+    /// One note at a time, in mono mode or while the legato footswitch (CC68) is down
+    pub fn is_mono(&self) -> bool {
+        self.mono || self.legato()
+    }
+
+    /// Legato footswitch (CC68), the sounding voices move to the next note without
+    /// being retriggered
+    pub fn legato(&self) -> bool {
+        self.cc[ControlFunction::LegatoFootswitch] >= 64
+    }
+
+    pub fn set_mono(&mut self, mono: bool) {
+        self.mono = mono;
+    }
+
+    pub fn note_priority(&self) -> NotePriority {
+        self.note_priority
+    }
+
+    pub fn set_note_priority(&mut self, note_priority: NotePriority) {
+        self.note_priority = note_priority;
+    }
+
+    pub fn push_key(&mut self, key: u8, vel: u8) {
+        self.remove_key(key);
+        self.held_keys.push((key, vel));
+    }
+
+    pub fn remove_key(&mut self, key: u8) {
+        self.held_keys.retain(|(k, _)| *k != key);
+    }
+
+    pub fn clear_keys(&mut self) {
+        self.held_keys.clear();
+    }
+
+    /// The held key that sounds in mono mode
+    pub fn priority_key(&self) -> Option<u8> {
+        self.priority_note().map(|(key, _)| key)
+    }
+
+    /// The held key that sounds in mono mode, with its velocity
+    pub fn priority_note(&self) -> Option<(u8, u8)> {
+        let keys = self.held_keys.iter().copied();
+        match self.note_priority {
+            NotePriority::Last => keys.last(),
+            NotePriority::High => keys.max_by_key(|(key, _)| *key),
+            NotePriority::Low => keys.min_by_key(|(key, _)| *key),
+        }
+    }
+
+    /// Sostenuto pedal (CC66)
+    pub fn sostenuto(&self) -> bool {
+        self.cc[ControlFunction::Sostenuto] >= 64
+    }
+
+    pub fn set_portamento_control(&mut self, key: u8) {
+        self.portamento_control = Some(key);
+    }
+
+    /// Source key of the glide to `key`, `key` becomes the previous key of the channel.
+    ///
+    /// The portamento control key (CC84) is used once, otherwise the previous key
+    /// if portamento is on.
+    pub fn portamento_from_key(&mut self, key: u8) -> Option<u8> {
+        let from_key = match self.portamento_control.take() {
+            Some(from_key) => Some(from_key),
+            None if self.portamento() => self.prev_key,
+            None => None,
+        };
+        self.prev_key = Some(key);
+
+        from_key.filter(|_| self.portamento_time() > 0)
     }
 
     pub fn nrpn_select(&self) -> i16 {
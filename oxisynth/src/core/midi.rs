@@ -7,6 +7,7 @@ use crate::core::soundfont::{
     generator::{gen_scale_nrpn, GeneratorType},
     InstrumentZone, PresetZone,
 };
+use crate::core::tuning_bank::TuningBank;
 use crate::core::voice_pool::{ModulateCtrl, Voice, VoiceAddMode, VoiceDescriptor, VoicePool};
 use crate::midi_event::ControlFunction;
 use crate::{MidiEvent, OxiError};
@@ -16,20 +17,25 @@ use super::Core;
 pub(crate) fn handle_event(synth: &mut Core, event: MidiEvent) -> Result<(), OxiError> {
     match event.check()? {
         MidiEvent::NoteOn { channel, key, vel } => {
+            let gain = synth.gain();
             self::noteon(
-                synth.channels.get(channel as usize)?,
+                synth.channels.get_mut(channel as usize)?,
                 &mut synth.voices,
                 synth.ticks,
                 synth.settings.min_note_length_ticks,
-                synth.settings.gain,
+                gain,
                 key,
                 vel,
             )?;
         }
         MidiEvent::NoteOff { channel, key } => {
-            synth.voices.noteoff(
-                synth.channels.get(channel as usize)?,
+            let gain = synth.gain();
+            self::noteoff(
+                synth.channels.get_mut(channel as usize)?,
+                &mut synth.voices,
+                synth.ticks,
                 synth.settings.min_note_length_ticks,
+                gain,
                 key,
             );
         }
@@ -41,6 +47,7 @@ pub(crate) fn handle_event(synth: &mut Core, event: MidiEvent) -> Result<(), Oxi
             self::cc(
                 synth.channels.get_mut(channel as usize)?,
                 &mut synth.voices,
+                &synth.tunings,
                 synth.settings.min_note_length_ticks,
                 synth.settings.drums_channel_active,
                 ctrl,
@@ -48,12 +55,14 @@ pub(crate) fn handle_event(synth: &mut Core, event: MidiEvent) -> Result<(), Oxi
             );
         }
         MidiEvent::AllNotesOff { channel } => {
-            synth.voices.all_notes_off(
-                synth.channels.get_mut(channel as usize)?,
-                synth.settings.min_note_length_ticks,
-            );
+            let channel = synth.channels.get_mut(channel as usize)?;
+            channel.clear_keys();
+            synth
+                .voices
+                .all_notes_off(channel, synth.settings.min_note_length_ticks);
         }
         MidiEvent::AllSoundOff { channel } => {
+            synth.channels.get_mut(channel as usize)?.clear_keys();
             synth.voices.all_sounds_off(channel as usize);
         }
         MidiEvent::PitchBend { channel, value } => {
@@ -93,22 +102,30 @@ pub(crate) fn handle_event(synth: &mut Core, event: MidiEvent) -> Result<(), Oxi
             synth.voices.key_pressure(channel, key);
         }
         MidiEvent::SystemReset => {
-            synth.voices.system_reset();
-
-            let preset = synth.font_bank.find_preset(0, 0).map(|p| p.1);
-            for channel in synth.channels.iter_mut() {
-                channel.init(preset.clone());
-                channel.init_ctrl(false);
-            }
-
-            synth.chorus.reset();
-            synth.reverb.reset();
+            self::system_reset(synth);
         }
     };
 
     Ok(())
 }
 
+/// Turn all voices off, and reset the channels, effects and master state to their defaults
+pub(crate) fn system_reset(synth: &mut Core) {
+    synth.voices.system_reset();
+
+    let preset = synth.font_bank.find_preset(0, 0).map(|p| p.1);
+    for channel in synth.channels.iter_mut() {
+        channel.init(preset.clone());
+        channel.init_ctrl(false);
+    }
+
+    synth.chorus.reset();
+    synth.reverb.reset();
+
+    synth.master = Default::default();
+    synth.update_master();
+}
+
 type MidiControlChange = u32;
 const RPN_MSB: MidiControlChange = 101;
 const RPN_LSB: MidiControlChange = 100;
@@ -137,7 +154,7 @@ pub(crate) fn set_gen(
 
 /// Send a noteon message.
 fn noteon(
-    channel: &Channel,
+    channel: &mut Channel,
     voices: &mut VoicePool,
     start_time: usize,
     min_note_length_ticks: usize,
@@ -146,19 +163,82 @@ fn noteon(
     vel: u8,
 ) -> Result<(), OxiError> {
     if vel == 0 {
-        voices.noteoff(channel, min_note_length_ticks, key);
+        noteoff(
+            channel,
+            voices,
+            start_time,
+            min_note_length_ticks,
+            gain,
+            key,
+        );
         Ok(())
     } else if channel.preset().is_none() {
         Err(OxiError::ChannelHasNoPreset)
     } else {
+        let sounding_key = channel.priority_key();
+        channel.push_key(key, vel);
+
+        // In mono mode a key with a lower priority than the sounding one stays silent
+        if channel.is_mono() && channel.priority_key() != Some(key) {
+            return Ok(());
+        }
+
         voices.release_voice_on_same_note(channel, key, min_note_length_ticks);
-        voices.noteid_add();
 
-        inner_noteon(channel, voices, start_time, gain, key, vel);
+        let portamento_key = channel.portamento_from_key(key);
+        if let Some(sounding_key) = sounding_key.filter(|_| channel.is_mono()) {
+            // With legato the sounding voices move to the new key, otherwise they are released
+            // and the new note is retriggered
+            if channel.legato() && voices.legato(channel, key, portamento_key) {
+                return Ok(());
+            }
+            voices.noteoff(channel, min_note_length_ticks, sounding_key);
+        }
+
+        voices.noteid_add();
+        inner_noteon(channel, voices, start_time, gain, key, vel, portamento_key);
         Ok(())
     }
 }
 
+/// Send a noteoff message.
+fn noteoff(
+    channel: &mut Channel,
+    voices: &mut VoicePool,
+    start_time: usize,
+    min_note_length_ticks: usize,
+    gain: f32,
+    key: u8,
+) {
+    let sounding_key = channel.priority_key();
+    channel.remove_key(key);
+
+    // In mono mode the next held key sounds again
+    if channel.is_mono() && sounding_key == Some(key) {
+        if let Some((next_key, next_vel)) = channel.priority_note() {
+            let portamento_key = channel.portamento_from_key(next_key);
+            if channel.legato() && voices.legato(channel, next_key, portamento_key) {
+                return;
+            }
+
+            voices.noteoff(channel, min_note_length_ticks, key);
+            voices.noteid_add();
+            inner_noteon(
+                channel,
+                voices,
+                start_time,
+                gain,
+                next_key,
+                next_vel,
+                portamento_key,
+            );
+            return;
+        }
+    }
+
+    voices.noteoff(channel, min_note_length_ticks, key);
+}
+
 fn inner_noteon(
     channel: &Channel,
     voices: &mut VoicePool,
@@ -166,6 +246,7 @@ fn inner_noteon(
     gain: f32,
     key: u8,
     vel: u8,
+    portamento_key: Option<u8>,
 ) {
     fn preset_zone_inside_range(zone: &PresetZone, key: u8, vel: u8) -> bool {
         zone.key_low <= key && zone.key_high >= key && zone.vel_low <= vel && zone.vel_high >= vel
@@ -212,6 +293,11 @@ fn inner_noteon(
                 continue;
             }
 
+            // Lazily loaded fonts read the sample here
+            let Some(sample) = sample.loaded() else {
+                continue;
+            };
+
             // this is a good zone. allocate a new synthesis process and initialize it
 
             // Initialize Voice
@@ -378,12 +464,13 @@ fn inner_noteon(
             };
 
             let desc = VoiceDescriptor {
-                sample: sample.clone(),
+                sample,
                 channel,
                 key,
                 vel,
                 start_time,
                 gain,
+                portamento_key,
             };
 
             let voice_id = voices.request_new_voice(desc, init);
@@ -411,6 +498,7 @@ fn inner_noteon(
 fn cc(
     channel: &mut Channel,
     voices: &mut VoicePool,
+    tunings: &TuningBank,
     min_note_length_ticks: usize,
     drums_channel_active: bool,
     num: u8,
@@ -420,6 +508,7 @@ fn cc(
         return;
     };
 
+    let prev_value = channel.cc(num as usize);
     *channel.cc_mut(num as usize) = value;
 
     use ControlFunction::*;
@@ -434,6 +523,15 @@ fn cc(
             }
         }
 
+        Sostenuto => {
+            // Only the notes that are down when the pedal goes down are held
+            if value >= 64 && prev_value < 64 {
+                voices.sostenuto_on(channel);
+            } else if value < 64 && prev_value >= 64 {
+                voices.sostenuto_off(channel, min_note_length_ticks);
+            }
+        }
+
         BankSelect => {
             if channel.id() == 9 && drums_channel_active {
                 // ignored
@@ -465,16 +563,30 @@ fn cc(
                 .set_banknum((value as u32 & 0x7f).wrapping_add((channel.bank_msb() as u32) << 7));
         }
 
+        PortamentoControl => {
+            channel.set_portamento_control(value & 0x7F);
+        }
+
         AllNotesOff => {
+            channel.clear_keys();
             voices.all_notes_off(channel, min_note_length_ticks);
         }
 
         AllSoundOff => {
+            channel.clear_keys();
+            voices.all_notes_off(channel, min_note_length_ticks);
+        }
+
+        // Mode messages also turn all notes off
+        MonoOperation | PolyOperation => {
+            channel.set_mono(num == MonoOperation);
+            channel.clear_keys();
             voices.all_notes_off(channel, min_note_length_ticks);
         }
 
         ResetAllControllers => {
             channel.init_ctrl(true);
+            voices.sostenuto_off(channel, min_note_length_ticks);
             voices.modulate_voices_all(channel);
         }
 
@@ -525,12 +637,17 @@ fn cc(
                             (value - 64) as f32,
                         );
                     }
-                    // TODO: This is fishy, for some reason those are missing from FluidLite, but
-                    // are present in Fluidsynth
-                    // https://github.com/FluidSynth/fluidsynth/blob/fa5173cbaefed60121db057bad7be7686165f7cc/src/synth/fluid_synth.c#L1857
-
-                    // RPN_TUNING_PROGRAM_CHANGE | RPN_TUNING_BANK_SELECT | RPN_MODULATION_DEPTH_RANGE
-                    // 3 | 4 | 5 => {}
+                    // RPN_TUNING_PROGRAM_CHANGE
+                    3 => {
+                        let bank = channel.tuning_bank();
+                        let tuning = tunings.get(bank, value).map(|e| e.tuning);
+                        channel.select_tuning(bank, value, tuning);
+                        voices.modulate_voices(channel, ModulateCtrl::Tuning);
+                    }
+                    // RPN_TUNING_BANK_SELECT
+                    4 => channel.set_tuning_bank(value),
+                    // TODO: RPN_MODULATION_DEPTH_RANGE
+                    // 5 => {}
                     _ => {}
                 }
             }
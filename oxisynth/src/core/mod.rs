@@ -1,4 +1,7 @@
+use std::collections::VecDeque;
+
 pub(crate) mod midi;
+pub(crate) mod sysex;
 pub(crate) mod write;
 
 mod channel_pool;
@@ -7,31 +10,37 @@ mod settings;
 mod voice_pool;
 
 mod conv;
-pub use settings::InterpolationMethod;
 pub(crate) use settings::Settings;
+pub use settings::{InterpolationMethod, NotePriority};
 
 mod font_bank;
+mod tuning_bank;
 
 use oxisynth_chorus::Chorus;
 use oxisynth_reverb::Reverb;
 
 mod soundfont;
-pub use soundfont::{generator::GeneratorType, Preset, SoundFont};
-
-use voice_pool::VoicePool;
+pub use soundfont::{
+    generator::GeneratorType, BrokenSamples, Instrument, InstrumentDescriptor, InstrumentId,
+    InstrumentZone, LoadOptions, Mod, Preset, PresetDescriptor, PresetZone, Sample,
+    SampleDescriptor, SampleId, SoundFont, SoundFontBuilder, ZoneDescriptor,
+};
+
+use sysex::MasterState;
+pub(crate) use voice_pool::ModulateCtrl;
+use voice_pool::{SoftPedalParams, VoicePool};
 use write::OutputBuffer;
 
 use self::channel_pool::ChannelPool;
 use self::font_bank::FontBank;
+use self::tuning_bank::TuningBank;
 
-use crate::{SettingsError, SynthDescriptor};
-
-const BUFSIZE: usize = 64;
-const BUFSIZE_F32: f32 = BUFSIZE as f32;
+use crate::{MidiEvent, SettingsError, SynthDescriptor};
 
 pub(crate) struct Core {
     ticks: usize,
     pub font_bank: FontBank,
+    pub tunings: TuningBank,
 
     pub channels: ChannelPool,
     pub voices: VoicePool,
@@ -40,6 +49,10 @@ pub(crate) struct Core {
     pub chorus: Chorus,
 
     pub settings: Settings,
+    pub master: MasterState,
+
+    /// Timed events waiting for their block, by frame
+    events: VecDeque<(usize, MidiEvent)>,
 
     output: OutputBuffer,
 }
@@ -64,16 +77,29 @@ impl Core {
             ticks: 0,
 
             font_bank: FontBank::new(),
+            tunings: TuningBank::new(),
 
             channels: ChannelPool::new(settings.midi_channels as usize, settings.interpolation),
-            voices: VoicePool::new(settings.polyphony as usize, settings.sample_rate),
-
-            output: OutputBuffer::new(nbuf as usize),
+            voices: VoicePool::new(
+                settings.polyphony as usize,
+                settings.sample_rate,
+                settings.block_size,
+                SoftPedalParams {
+                    attenuation: settings.soft_pedal_attenuation,
+                    cutoff: settings.soft_pedal_cutoff,
+                },
+                settings.hold2_release,
+            ),
+
+            output: OutputBuffer::new(nbuf as usize, settings.block_size),
 
             reverb: Reverb::new(),
             chorus: Chorus::new(settings.sample_rate),
 
             settings,
+            master: MasterState::default(),
+
+            events: VecDeque::new(),
         };
 
         if synth.settings.drums_channel_active {
@@ -82,4 +108,25 @@ impl Core {
 
         Ok(synth)
     }
+
+    /// Update the channels that use the tuning `(bank, program)` after it was changed.
+    ///
+    /// With `apply` the notes that are playing on those channels are retuned too.
+    pub(crate) fn refresh_tuning(&mut self, bank: u8, program: u8, apply: bool) {
+        let Some(entry) = self.tunings.get(bank, program) else {
+            return;
+        };
+
+        for channel in self
+            .channels
+            .iter_mut()
+            .filter(|ch| ch.tuning_id() == Some((bank, program)))
+        {
+            channel.select_tuning(bank, program, Some(entry.tuning));
+
+            if apply {
+                self.voices.modulate_voices(channel, ModulateCtrl::Tuning);
+            }
+        }
+    }
 }
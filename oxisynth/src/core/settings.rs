@@ -14,6 +14,18 @@ pub enum InterpolationMethod {
     SeventhOrder = 7,
 }
 
+/// Which of the held keys sounds on a channel in mono mode
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NotePriority {
+    /// The last played key (default)
+    #[default]
+    Last,
+    /// The highest held key
+    High,
+    /// The lowest held key
+    Low,
+}
+
 pub(crate) struct Settings {
     pub reverb_active: bool,
     pub chorus_active: bool,
@@ -52,6 +64,26 @@ pub(crate) struct Settings {
     /// Min: 0
     /// Max: 65535
     pub min_note_length: u16,
+    /// Def: 64
+    /// Min: 16
+    /// Max: 1024
+    pub block_size: usize,
+    /// Def: 16
+    /// Min: 0
+    /// Max: 126
+    pub device_id: u8,
+    /// Def: 60.0
+    /// Min: 0.0
+    /// Max: 1440.0
+    pub soft_pedal_attenuation: f32,
+    /// Def: 1200.0
+    /// Min: 0.0
+    /// Max: 12000.0
+    pub soft_pedal_cutoff: f32,
+    /// Def: 2400.0
+    /// Min: 0.0
+    /// Max: 12000.0
+    pub hold2_release: f32,
 
     pub min_note_length_ticks: usize,
 }
@@ -86,6 +118,20 @@ static GAIN_RANGE: Range<f32> = Range {
 };
 static AUDIO_CHANNELS_RANGE: Range<u8> = Range { min: 1, max: 128 };
 static AUDIO_GROUPS_RANGE: Range<u8> = Range { min: 1, max: 128 };
+static BLOCK_SIZE_RANGE: Range<u16> = Range { min: 16, max: 1024 };
+static DEVICE_ID_RANGE: Range<u8> = Range { min: 0, max: 126 };
+static SOFT_PEDAL_ATTENUATION_RANGE: Range<f32> = Range {
+    min: 0.0,
+    max: 1440.0,
+};
+static SOFT_PEDAL_CUTOFF_RANGE: Range<f32> = Range {
+    min: 0.0,
+    max: 12000.0,
+};
+static HOLD2_RELEASE_RANGE: Range<f32> = Range {
+    min: 0.0,
+    max: 12000.0,
+};
 static SAMPLE_RATE_RANGE: Range<f32> = Range {
     min: 8000.0,
     max: 96000.0,
@@ -137,6 +183,26 @@ impl TryFrom<SynthDescriptor> for Settings {
         // Guarded by type system
         let min_note_length = desc.min_note_length;
 
+        let block_size = BLOCK_SIZE_RANGE
+            .check(desc.block_size)
+            .map_err(SettingsError::BlockSizeRange)? as usize;
+
+        let device_id = DEVICE_ID_RANGE
+            .check(desc.device_id)
+            .map_err(SettingsError::DeviceIdRange)?;
+
+        let soft_pedal_attenuation = SOFT_PEDAL_ATTENUATION_RANGE
+            .check(desc.soft_pedal_attenuation)
+            .map_err(SettingsError::SoftPedalAttenuationRange)?;
+
+        let soft_pedal_cutoff = SOFT_PEDAL_CUTOFF_RANGE
+            .check(desc.soft_pedal_cutoff)
+            .map_err(SettingsError::SoftPedalCutoffRange)?;
+
+        let hold2_release = HOLD2_RELEASE_RANGE
+            .check(desc.hold2_release)
+            .map_err(SettingsError::Hold2ReleaseRange)?;
+
         let mut settings = Self {
             reverb_active: desc.reverb_active,
             chorus_active: desc.chorus_active,
@@ -150,6 +216,11 @@ impl TryFrom<SynthDescriptor> for Settings {
             audio_groups,
             sample_rate,
             min_note_length,
+            block_size,
+            device_id,
+            soft_pedal_attenuation,
+            soft_pedal_cutoff,
+            hold2_release,
 
             min_note_length_ticks: 0,
         };
@@ -0,0 +1,312 @@
+use std::ops::{Range, RangeInclusive};
+
+use soundfont::raw::{
+    Generator, GeneratorAmount, GeneratorAmountRange, GeneratorType as RawGeneratorType, Info,
+    InstrumentHeader, Modulator, PresetHeader, SampleData as SampleChunks, SampleHeader,
+    SampleLink, Version,
+};
+use soundfont::SfEnum;
+
+use crate::error::LoadError;
+use crate::GeneratorType;
+
+use super::{LoadOptions, SampleData, SampleSource, SoundFont};
+
+/// Zero points written after every sample, as required by the spec for the interpolators
+const SAMPLE_PADDING: usize = 46;
+
+/// Sample added to a [`SoundFontBuilder`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleId(usize);
+
+/// Instrument added to a [`SoundFontBuilder`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstrumentId(usize);
+
+/// Mono 16-bit PCM sample
+#[derive(Debug, Clone)]
+pub struct SampleDescriptor {
+    pub name: String,
+    pub data: Vec<i16>,
+    pub sample_rate: u32,
+    /// Loop points, relative to the first point of `data`
+    pub loop_range: Range<u32>,
+    /// MIDI key number at which the sample plays at its original pitch
+    pub origpitch: u8,
+    /// Pitch correction in cents
+    pub pitchadj: i8,
+}
+
+impl SampleDescriptor {
+    /// Sample at middle C, looped over all of its points
+    pub fn new(name: impl Into<String>, data: Vec<i16>, sample_rate: u32) -> Self {
+        Self {
+            name: name.into(),
+            loop_range: 0..data.len() as u32,
+            data,
+            sample_rate,
+            origpitch: 60,
+            pitchadj: 0,
+        }
+    }
+}
+
+/// Zone of an instrument or a preset
+#[derive(Debug, Clone)]
+pub struct ZoneDescriptor {
+    /// Def: 0..=127
+    pub key_range: RangeInclusive<u8>,
+    /// Def: 0..=127
+    pub vel_range: RangeInclusive<u8>,
+    /// Generators with their amount, as in a SoundFont file
+    ///
+    /// The key and velocity ranges, the instrument and the sample of the zone have their own
+    /// fields, they can't be set as generators.
+    pub generators: Vec<(GeneratorType, i16)>,
+    pub modulators: Vec<Modulator>,
+}
+
+impl Default for ZoneDescriptor {
+    fn default() -> Self {
+        Self {
+            key_range: 0..=127,
+            vel_range: 0..=127,
+            generators: Vec::new(),
+            modulators: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentDescriptor {
+    pub name: String,
+    /// Zone with the defaults of the other zones
+    pub global_zone: Option<ZoneDescriptor>,
+    pub zones: Vec<(SampleId, ZoneDescriptor)>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PresetDescriptor {
+    pub name: String,
+    pub bank: u16,
+    pub num: u8,
+    /// Zone with the defaults of the other zones
+    pub global_zone: Option<ZoneDescriptor>,
+    pub zones: Vec<(InstrumentId, ZoneDescriptor)>,
+}
+
+/// Creates a [`SoundFont`] in memory, without a SoundFont file
+///
+/// ```ignore
+/// let mut builder = SoundFontBuilder::new();
+///
+/// let sample = builder.add_sample(SampleDescriptor::new("Sine", points, 44100));
+/// let inst = builder.add_instrument(InstrumentDescriptor {
+///     name: "Sine".into(),
+///     global_zone: None,
+///     zones: vec![(sample, ZoneDescriptor::default())],
+/// });
+/// builder.add_preset(PresetDescriptor {
+///     name: "Sine".into(),
+///     zones: vec![(inst, ZoneDescriptor::default())],
+///     ..Default::default()
+/// });
+///
+/// synth.add_font(builder.build()?, true);
+/// ```
+#[derive(Default)]
+pub struct SoundFontBuilder {
+    points: Vec<i16>,
+    sample_headers: Vec<SampleHeader>,
+    instruments: Vec<soundfont::Instrument>,
+    presets: Vec<soundfont::Preset>,
+    /// First error of the descriptors, returned by [`Self::build()`]
+    error: Option<LoadError>,
+}
+
+impl SoundFontBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_sample(&mut self, sample: SampleDescriptor) -> SampleId {
+        let start = self.points.len() as u32;
+        let len = sample.data.len() as u32;
+
+        if sample.loop_range.start > sample.loop_range.end || sample.loop_range.end > len {
+            self.fail(format!(
+                "Loop {:?} of sample {:?} is outside of its {len} points",
+                sample.loop_range, sample.name
+            ));
+        }
+
+        self.points.extend_from_slice(&sample.data);
+        self.points.resize(self.points.len() + SAMPLE_PADDING, 0);
+
+        self.sample_headers.push(SampleHeader {
+            name: sample.name,
+            start,
+            end: start + len,
+            loop_start: start + sample.loop_range.start,
+            loop_end: start + sample.loop_range.end,
+            sample_rate: sample.sample_rate,
+            origpitch: sample.origpitch,
+            pitchadj: sample.pitchadj,
+            sample_link: 0,
+            sample_type: SampleLink::MonoSample,
+        });
+
+        SampleId(self.sample_headers.len() - 1)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if a sample of the zones was not added to this builder.
+    pub fn add_instrument(&mut self, instrument: InstrumentDescriptor) -> InstrumentId {
+        let mut zones = Vec::new();
+        if let Some(zone) = &instrument.global_zone {
+            zones.push(self.zone(zone, None));
+        }
+        for (sample, zone) in instrument.zones.iter() {
+            assert!(
+                sample.0 < self.sample_headers.len(),
+                "Sample {sample:?} was not added to this builder"
+            );
+            zones.push(self.zone(zone, Some((RawGeneratorType::SampleID, sample.0))));
+        }
+
+        self.instruments.push(soundfont::Instrument {
+            header: InstrumentHeader {
+                name: instrument.name,
+                bag_id: 0,
+            },
+            zones,
+        });
+
+        InstrumentId(self.instruments.len() - 1)
+    }
+
+    /// # Panics
+    ///
+    /// Panics if an instrument of the zones was not added to this builder.
+    pub fn add_preset(&mut self, preset: PresetDescriptor) {
+        let mut zones = Vec::new();
+        if let Some(zone) = &preset.global_zone {
+            zones.push(self.zone(zone, None));
+        }
+        for (instrument, zone) in preset.zones.iter() {
+            assert!(
+                instrument.0 < self.instruments.len(),
+                "Instrument {instrument:?} was not added to this builder"
+            );
+            zones.push(self.zone(zone, Some((RawGeneratorType::Instrument, instrument.0))));
+        }
+
+        self.presets.push(soundfont::Preset {
+            header: PresetHeader {
+                name: preset.name,
+                preset: preset.num as u16,
+                bank: preset.bank,
+                bag_id: 0,
+                library: 0,
+                genre: 0,
+                morphology: 0,
+            },
+            zones,
+        });
+    }
+
+    /// Create the font, it can be added to the synth with [`crate::Synth::add_font()`].
+    pub fn build(self) -> Result<SoundFont, LoadError> {
+        if let Some(error) = self.error {
+            return Err(error);
+        }
+
+        let sf2 = soundfont::SoundFont2 {
+            info: Info {
+                version: Version { major: 2, minor: 4 },
+                sound_engine: "EMU8000".into(),
+                bank_name: String::new(),
+                rom_name: None,
+                rom_version: None,
+                creation_date: None,
+                engineers: None,
+                product: None,
+                copyright: None,
+                comments: None,
+                software: None,
+            },
+            presets: self.presets,
+            instruments: self.instruments,
+            sample_headers: self.sample_headers,
+            sample_data: SampleChunks {
+                smpl: None,
+                sm24: None,
+            },
+        };
+
+        let data = SampleData::new(self.points.into());
+        SoundFont::import(&sf2, SampleSource::Loaded(data), LoadOptions::default())
+    }
+
+    /// Generators of `zone` in the order of the spec, the ranges first and the instrument or
+    /// sample `id` last
+    fn zone(
+        &mut self,
+        zone: &ZoneDescriptor,
+        id: Option<(RawGeneratorType, usize)>,
+    ) -> soundfont::Zone {
+        let range = |ty: RawGeneratorType, range: &RangeInclusive<u8>| Generator {
+            ty: SfEnum::Value(ty),
+            amount: GeneratorAmount::Range(GeneratorAmountRange {
+                low: *range.start(),
+                high: *range.end(),
+            }),
+        };
+
+        let mut gen_list = vec![
+            range(RawGeneratorType::KeyRange, &zone.key_range),
+            range(RawGeneratorType::VelRange, &zone.vel_range),
+        ];
+
+        for &(ty, amount) in zone.generators.iter() {
+            let raw = match ty {
+                GeneratorType::KeyRange
+                | GeneratorType::VelRange
+                | GeneratorType::Instrument
+                | GeneratorType::SampleId
+                | GeneratorType::Pitch => None,
+                _ => RawGeneratorType::try_from(ty as u16).ok(),
+            };
+
+            match raw {
+                Some(raw) => gen_list.push(Generator {
+                    ty: SfEnum::Value(raw),
+                    amount: GeneratorAmount::I16(amount),
+                }),
+                None => self.fail(format!("Generator {ty:?} can't be set in a zone")),
+            }
+        }
+
+        if let Some((ty, id)) = id {
+            match u16::try_from(id) {
+                Ok(id) => gen_list.push(Generator {
+                    ty: SfEnum::Value(ty),
+                    amount: GeneratorAmount::U16(id),
+                }),
+                Err(_) => self.fail(format!("{ty:?} {id} does not fit in 16 bits")),
+            }
+        }
+
+        soundfont::Zone {
+            mod_list: zone.modulators.clone(),
+            gen_list,
+        }
+    }
+
+    fn fail(&mut self, reason: String) {
+        if self.error.is_none() {
+            self.error = Some(LoadError::InvalidFont { reason });
+        }
+    }
+}
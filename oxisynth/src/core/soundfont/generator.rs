@@ -218,6 +218,13 @@ impl GeneratorList {
 
         out
     }
+
+    /// Generators set by a zone, with their amount
+    pub(crate) fn set(&self) -> impl Iterator<Item = (GeneratorType, f64)> + '_ {
+        GeneratorType::iter()
+            .filter(|ty| self[*ty].flags != 0)
+            .map(|ty| (ty, self[ty].val))
+    }
 }
 
 impl std::ops::Index<GeneratorType> for GeneratorList {
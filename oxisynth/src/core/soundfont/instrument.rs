@@ -1,3 +1,5 @@
+use std::ops::RangeInclusive;
+
 use crate::error::LoadError;
 use crate::GeneratorType;
 
@@ -7,16 +9,16 @@ use super::Sample;
 
 const GEN_SET: u32 = 1;
 
+/// Instrument of a [`PresetZone`](super::PresetZone), playing samples over key and velocity ranges
 #[derive(Clone, Debug)]
 pub struct Instrument {
-    _name: String,
+    name: String,
     global_zone: Option<InstrumentZone>,
     zones: Vec<InstrumentZone>,
 }
 
 impl Instrument {
-    pub fn import(
-        sf2: &soundfont::SoundFont2,
+    pub(crate) fn import(
         inst: &soundfont::Instrument,
         samples: &[Sample],
     ) -> Result<Self, LoadError> {
@@ -31,7 +33,7 @@ impl Instrument {
 
         for (id, zone) in inst.zones.iter().enumerate() {
             let name = format!("{}/{}", inst.header.name, id);
-            let zone = InstrumentZone::import(name, sf2, zone, samples)?;
+            let zone = InstrumentZone::import(name, zone, samples)?;
             if id == 0 && zone.sample.is_none() {
                 global_zone = Some(zone);
             } else {
@@ -40,12 +42,17 @@ impl Instrument {
         }
 
         Ok(Self {
-            _name: name,
+            name,
             global_zone,
             zones,
         })
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Zone with the defaults of the other zones of the instrument
     pub fn global_zone(&self) -> Option<&InstrumentZone> {
         self.global_zone.as_ref()
     }
@@ -55,23 +62,23 @@ impl Instrument {
     }
 }
 
+/// Zone of an [`Instrument`], playing a sample over a key and velocity range
 #[derive(Clone, Debug)]
 #[repr(C)]
 pub struct InstrumentZone {
-    pub name: String,
-    pub sample: Option<Sample>,
-    pub key_low: u8,
-    pub key_high: u8,
-    pub vel_low: u8,
-    pub vel_high: u8,
-    pub gen: GeneratorList,
-    pub mods: Vec<Mod>,
+    pub(crate) name: String,
+    pub(crate) sample: Option<Sample>,
+    pub(crate) key_low: u8,
+    pub(crate) key_high: u8,
+    pub(crate) vel_low: u8,
+    pub(crate) vel_high: u8,
+    pub(crate) gen: GeneratorList,
+    pub(crate) mods: Vec<Mod>,
 }
 
 impl InstrumentZone {
     fn import(
         name: String,
-        sf2: &soundfont::SoundFont2,
         zone: &soundfont::Zone,
         samples: &[Sample],
     ) -> Result<InstrumentZone, LoadError> {
@@ -113,21 +120,16 @@ impl InstrumentZone {
         }
 
         let sample = if let Some(sample_id) = zone.sample() {
-            let sample = sf2.sample_headers.get(*sample_id as usize).unwrap();
-            let name = &sample.name;
-
-            // Find Sample by name:
-            let sample = samples
-                .iter()
-                .find(|sample| sample.name() == name.as_str())
-                .cloned();
-
-            if sample.is_none() {
-                log::error!("Couldn't find sample: {name:?}");
-                return Err(LoadError::SampleNotFound { name: name.clone() });
-            }
+            // The samples are imported in the order of their headers,
+            // their names don't have to be unique
+            let Some(sample) = samples.get(*sample_id as usize) else {
+                log::error!("Couldn't find sample #{sample_id} of zone {name:?}");
+                return Err(LoadError::SampleNotFound {
+                    name: format!("#{sample_id}"),
+                });
+            };
 
-            sample
+            Some(sample.clone())
         } else {
             None
         };
@@ -154,4 +156,28 @@ impl InstrumentZone {
             mods,
         })
     }
+
+    /// Sample of the zone, `None` for the global zone
+    pub fn sample(&self) -> Option<&Sample> {
+        self.sample.as_ref()
+    }
+
+    pub fn key_range(&self) -> RangeInclusive<u8> {
+        self.key_low..=self.key_high.min(127)
+    }
+
+    pub fn vel_range(&self) -> RangeInclusive<u8> {
+        self.vel_low..=self.vel_high.min(127)
+    }
+
+    /// Generators set by the zone, with their amount
+    ///
+    /// The key and velocity ranges, and the sample, are not included.
+    pub fn generators(&self) -> impl Iterator<Item = (GeneratorType, f64)> + '_ {
+        self.gen.set()
+    }
+
+    pub fn modulators(&self) -> &[Mod] {
+        &self.mods
+    }
 }
@@ -1,9 +1,11 @@
+mod builder;
 pub mod generator;
 mod instrument;
 pub(crate) mod modulator;
 mod preset;
 mod sample;
 mod sample_data;
+mod sample_store;
 
 use std::{
     io::{Read, Seek},
@@ -13,15 +15,44 @@ use std::{
 use crate::error::LoadError;
 
 pub(crate) use {
-    instrument::InstrumentZone, preset::PresetZone, sample::Sample, sample_data::SampleData,
+    sample::SampleSource,
+    sample_data::{SampleData, SamplePoints},
+    sample_store::SampleStore,
 };
 
-pub use preset::Preset;
+pub use {
+    builder::{
+        InstrumentDescriptor, InstrumentId, PresetDescriptor, SampleDescriptor, SampleId,
+        SoundFontBuilder, ZoneDescriptor,
+    },
+    instrument::{Instrument, InstrumentZone},
+    modulator::Mod,
+    preset::{Preset, PresetZone},
+    sample::Sample,
+};
 
 pub struct SoundFont {
     presets: Vec<Arc<Preset>>,
 }
 
+/// What to do with compressed samples that fail to decode
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BrokenSamples {
+    /// Fail to load the font with [`LoadError::SampleDecode`]
+    #[default]
+    Fail,
+    /// Load the font without the sample, the zones that use it do not play
+    Skip,
+    /// Replace the sample with silence
+    Silence,
+}
+
+/// Options of [`SoundFont::load_with_options()`] and [`SoundFont::load_lazy()`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LoadOptions {
+    pub broken_samples: BrokenSamples,
+}
+
 // SondFont::load() might be slow due to IO or SF3 vorbis decompression,
 // so we want to allow the font to be loaded on a different thread.
 //
@@ -37,6 +68,60 @@ impl SoundFont {
     /// This operation might be quite slow due to blocking IO operations and potential SF3 vorbis decompression,
     /// so you might consider loading on a secondary thread. [SoundFont] is both [Send] and [Sync].
     pub fn load<F: Read + Seek>(file: &mut F) -> Result<Self, LoadError> {
+        Self::load_with_options(file, LoadOptions::default())
+    }
+
+    /// Load SoundFont™ file, see [`SoundFont::load()`].
+    pub fn load_with_options<F: Read + Seek>(
+        file: &mut F,
+        options: LoadOptions,
+    ) -> Result<Self, LoadError> {
+        let sf2 = Self::load_sf2(file)?;
+
+        let (smpl, sm24) = Self::sample_chunks(&sf2);
+        let sample_data = SampleData::load(file, smpl, sm24)?;
+
+        Self::import(&sf2, SampleSource::Loaded(sample_data), options)
+    }
+
+    /// Load the presets of a SoundFont™ file, and read its samples on demand.
+    ///
+    /// The samples of a preset are read from `file` when the preset is selected on a channel,
+    /// and when a note is started. At most `cache_limit` bytes of samples are kept cached,
+    /// samples that are still playing stay in memory until their voices end.
+    ///
+    /// `file` can be a memory-mapped file, through [`std::io::Cursor`]. Reading the samples
+    /// blocks the synth, so the file should be fast to read.
+    ///
+    /// Compressed SF3 samples are decoded when the font is loaded, as with [`SoundFont::load()`].
+    pub fn load_lazy<F: Read + Seek + Send + 'static>(
+        mut file: F,
+        cache_limit: usize,
+        options: LoadOptions,
+    ) -> Result<Self, LoadError> {
+        let sf2 = Self::load_sf2(&mut file)?;
+
+        if sf2.info.version.major > 2 {
+            let (smpl, sm24) = Self::sample_chunks(&sf2);
+            let sample_data = SampleData::load(&mut file, smpl, sm24)?;
+            return Self::import(&sf2, SampleSource::Loaded(sample_data), options);
+        }
+
+        let (smpl, sm24) = Self::sample_chunks(&sf2);
+        let sm24 = SampleData::valid_sm24(smpl, sm24);
+        let store = SampleStore::new(Box::new(file), *smpl, sm24.copied(), cache_limit);
+
+        Self::import(
+            &sf2,
+            SampleSource::Lazy {
+                store: Arc::new(store),
+                noise_floor: Default::default(),
+            },
+            options,
+        )
+    }
+
+    fn load_sf2<F: Read + Seek>(file: &mut F) -> Result<soundfont::SoundFont2, LoadError> {
         let sf2 = soundfont::SoundFont2::load(file)?;
 
         #[cfg(feature = "sf3")]
@@ -51,26 +136,55 @@ impl SoundFont {
             });
         }
 
-        let sf2 = sf2.sort_presets();
+        Ok(sf2.sort_presets())
+    }
 
-        let sample_data = SampleData::load(file, sf2.sample_data.smpl.as_ref().unwrap())?;
+    /// The smpl chunk, and the sm24 chunk if the version supports it
+    fn sample_chunks(
+        sf2: &soundfont::SoundFont2,
+    ) -> (
+        &soundfont::raw::SampleChunk,
+        Option<&soundfont::raw::SampleChunk>,
+    ) {
+        // 24-bit samples were added in version 2.04
+        let version = &sf2.info.version;
+        let sm24 = if version.major == 2 && version.minor >= 4 {
+            sf2.sample_data.sm24.as_ref()
+        } else {
+            None
+        };
 
-        let mut samples = Vec::new();
+        (sf2.sample_data.smpl.as_ref().unwrap(), sm24)
+    }
 
-        for sfsample in sf2.sample_headers.iter() {
-            let sample = Sample::import(sfsample, sample_data.clone())?;
-            samples.push(sample);
-        }
+    fn import(
+        sf2: &soundfont::SoundFont2,
+        source: SampleSource,
+        options: LoadOptions,
+    ) -> Result<Self, LoadError> {
+        #[cfg(feature = "sf3-parallel")]
+        let samples = if sf2.sample_headers.iter().any(|h| h.sample_type.is_vorbis()) {
+            import_samples_parallel(&sf2.sample_headers, &source, options)?
+        } else {
+            import_samples(&sf2.sample_headers, &source, options)?
+        };
+        #[cfg(not(feature = "sf3-parallel"))]
+        let samples = import_samples(&sf2.sample_headers, &source, options)?;
 
         let mut presets = Vec::new();
         for sfpreset in sf2.presets.iter() {
-            let preset = Preset::import(&sf2, sfpreset, &samples)?;
+            let preset = Preset::import(sf2, sfpreset, &samples)?;
             presets.push(Arc::new(preset));
         }
 
         Ok(Self { presets })
     }
 
+    /// Presets of the font, in file order
+    pub fn presets(&self) -> impl Iterator<Item = &Preset> {
+        self.presets.iter().map(|preset| &**preset)
+    }
+
     pub(crate) fn preset(&self, bank: u32, prenum: u8) -> Option<Arc<Preset>> {
         self.presets
             .iter()
@@ -78,3 +192,524 @@ impl SoundFont {
             .cloned()
     }
 }
+
+fn import_samples(
+    headers: &[soundfont::raw::SampleHeader],
+    source: &SampleSource,
+    options: LoadOptions,
+) -> Result<Vec<Sample>, LoadError> {
+    headers
+        .iter()
+        .map(|header| {
+            let source = match source {
+                // Every sample scans its own loop
+                SampleSource::Lazy { store, .. } => SampleSource::Lazy {
+                    store: store.clone(),
+                    noise_floor: Default::default(),
+                },
+                loaded => loaded.clone(),
+            };
+            Sample::import(header, source, options.broken_samples)
+        })
+        .collect()
+}
+
+/// Same as [`import_samples()`], with the SF3 samples decoded on all the cores
+#[cfg(feature = "sf3-parallel")]
+fn import_samples_parallel(
+    headers: &[soundfont::raw::SampleHeader],
+    source: &SampleSource,
+    options: LoadOptions,
+) -> Result<Vec<Sample>, LoadError> {
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_len = headers.len().div_ceil(threads).max(1);
+
+    let chunks = std::thread::scope(|scope| {
+        let handles: Vec<_> = headers
+            .chunks(chunk_len)
+            .map(|chunk| scope.spawn(move || import_samples(chunk, source, options)))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|err| std::panic::resume_unwind(err))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    // The chunks are in order, so the first error is the same as with the serial import
+    let mut samples = Vec::with_capacity(headers.len());
+    for chunk in chunks {
+        samples.extend(chunk?);
+    }
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{InterpolationMethod, MidiEvent, SoundFont, Synth, SynthDescriptor};
+
+    /// `sin.sf2` with a sm24 chunk filled with `lsb`, and the version set to 2.`minor`
+    fn sin_with_sm24(lsb: u8, minor: u16) -> Vec<u8> {
+        let data = std::fs::read("../testdata/sin.sf2").unwrap();
+        let u32_at = |pos: usize| u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap());
+
+        let mut out = data[..12].to_vec();
+        let mut pos = 12;
+        while pos < data.len() {
+            let len = u32_at(pos + 4) as usize;
+            let mut chunk = data[pos..pos + 8 + len + len % 2].to_vec();
+
+            match &chunk[8..12] {
+                b"INFO" => {
+                    let ifil = chunk.windows(4).position(|id| id == b"ifil").unwrap();
+                    chunk[ifil + 10..ifil + 12].copy_from_slice(&minor.to_le_bytes());
+                }
+                b"sdta" => {
+                    // The smpl chunk is the only one
+                    let points = u32_at(pos + 16) / 2;
+                    // Padded to an even size
+                    let sm24_len = points + points % 2;
+                    chunk.extend(b"sm24");
+                    chunk.extend(sm24_len.to_le_bytes());
+                    chunk.extend(vec![lsb; sm24_len as usize]);
+                    let list_len = chunk.len() as u32 - 8;
+                    chunk[4..8].copy_from_slice(&list_len.to_le_bytes());
+                }
+                _ => {}
+            }
+
+            out.extend(chunk);
+            pos += 8 + len + len % 2;
+        }
+
+        let riff_len = out.len() as u32 - 8;
+        out[4..8].copy_from_slice(&riff_len.to_le_bytes());
+        out
+    }
+
+    fn render(font: &[u8]) -> Vec<f32> {
+        render_font(
+            SoundFont::load(&mut std::io::Cursor::new(font)).unwrap(),
+            &[60],
+        )
+    }
+
+    fn render_font(font: SoundFont, keys: &[u8]) -> Vec<f32> {
+        render_font_with(font, keys, Default::default())
+    }
+
+    fn render_font_with(
+        font: SoundFont,
+        keys: &[u8],
+        interpolation: InterpolationMethod,
+    ) -> Vec<f32> {
+        let mut synth = Synth::new(SynthDescriptor {
+            reverb_active: false,
+            chorus_active: false,
+            interpolation,
+            ..Default::default()
+        })
+        .unwrap();
+        synth.add_font(font, true);
+
+        let mut samples = Vec::new();
+        for key in keys {
+            synth
+                .send_event(MidiEvent::NoteOn {
+                    channel: 0,
+                    key: *key,
+                    vel: 100,
+                })
+                .unwrap();
+
+            let mut block = vec![0.0; 2000];
+            synth.write(&mut block[..]);
+            samples.extend(block);
+        }
+        samples
+    }
+
+    #[test]
+    fn sm24() {
+        let reference = render(&std::fs::read("../testdata/sin.sf2").unwrap());
+
+        // Zero low bytes are the same as 16-bit samples
+        assert_eq!(render(&sin_with_sm24(0, 4)), reference);
+
+        let samples = render(&sin_with_sm24(0x80, 4));
+        assert_ne!(samples, reference);
+        // Half of the 16-bit step at most, amplified by the gain and the envelope
+        for (a, b) in samples.iter().zip(reference.iter()) {
+            assert!((a - b).abs() < 1.0 / 32768.0);
+        }
+
+        // Ignored before 2.04
+        assert_eq!(render(&sin_with_sm24(0x80, 1)), reference);
+    }
+
+    #[test]
+    fn load_lazy() {
+        let keys = [60, 64, 67, 72, 48];
+
+        let data = std::fs::read("../testdata/Boomwhacker.sf2").unwrap();
+        // The higher orders read points around the samples
+        for interpolation in [
+            InterpolationMethod::FourthOrder,
+            InterpolationMethod::SeventhOrder,
+        ] {
+            let reference = render_font_with(
+                SoundFont::load(&mut std::io::Cursor::new(&data)).unwrap(),
+                &keys,
+                interpolation,
+            );
+            assert!(reference.iter().any(|s| *s != 0.0));
+
+            // Without cache, with a cache smaller than the font, and with the whole font cached
+            for cache_limit in [0, 64 * 1024, usize::MAX] {
+                let font = SoundFont::load_lazy(
+                    std::io::Cursor::new(data.clone()),
+                    cache_limit,
+                    Default::default(),
+                );
+                assert_eq!(
+                    render_font_with(font.unwrap(), &keys, interpolation),
+                    reference
+                );
+            }
+        }
+
+        let data = sin_with_sm24(0x80, 4);
+        let font = SoundFont::load_lazy(std::io::Cursor::new(data.clone()), 0, Default::default())
+            .unwrap();
+        assert_eq!(render_font(font, &[60]), render(&data));
+    }
+
+    #[test]
+    fn points_outside_of_loaded_range() {
+        use super::SampleData;
+        use soundfont::raw::SampleChunk;
+
+        let data: Vec<u8> = (1..=20i16).flat_map(i16::to_le_bytes).collect();
+        let smpl = SampleChunk {
+            offset: 0,
+            len: data.len() as u32,
+        };
+        let data =
+            SampleData::load_range(&mut std::io::Cursor::new(data), &smpl, None, 5..10).unwrap();
+
+        let points = data.points();
+        assert_eq!(points.get(5), 6.0);
+        assert_eq!(points.get(9), 10.0);
+        assert_eq!(points.get(4), 0.0);
+        assert_eq!(points.get(10), 0.0);
+    }
+
+    #[cfg(feature = "sf3")]
+    #[test]
+    fn sf3_num_points() {
+        let samples = |path: &str| {
+            let font = SoundFont::load(&mut std::fs::File::open(path).unwrap()).unwrap();
+            let mut samples: Vec<_> = font
+                .presets()
+                .flat_map(|preset| preset.zones())
+                .filter_map(|zone| zone.instrument())
+                .flat_map(|inst| inst.zones())
+                .filter_map(|zone| zone.sample())
+                .map(|sample| (sample.name().to_string(), sample.num_points()))
+                .collect();
+            samples.sort();
+            samples.dedup();
+            samples
+        };
+
+        // The SF3 end is inclusive once decoded, the SF2 one is exclusive
+        let sf2 = samples("../testdata/Boomwhacker.sf2");
+        assert!(!sf2.is_empty());
+        assert_eq!(samples("../testdata/Boomwhacker.sf3"), sf2);
+    }
+
+    #[cfg(feature = "sf3")]
+    #[test]
+    fn broken_samples() {
+        use crate::{BrokenSamples, LoadError, LoadOptions};
+
+        let mut data = std::fs::read("../testdata/Boomwhacker.sf3").unwrap();
+        let ogg = data.windows(4).position(|id| id == b"OggS").unwrap();
+        data[ogg..ogg + 4].copy_from_slice(b"Oops");
+
+        let load = |broken_samples| {
+            SoundFont::load_with_options(
+                &mut std::io::Cursor::new(&data),
+                LoadOptions { broken_samples },
+            )
+        };
+
+        let Err(LoadError::SampleDecode { name, .. }) = load(BrokenSamples::Fail) else {
+            panic!("The broken sample should fail to load");
+        };
+        assert!(!name.is_empty());
+
+        let keys = [48, 60, 72];
+        let skipped = render_font(load(BrokenSamples::Skip).unwrap(), &keys);
+        let silenced = render_font(load(BrokenSamples::Silence).unwrap(), &keys);
+        assert_eq!(skipped, silenced);
+        // The other samples still play
+        assert!(skipped.iter().any(|s| *s != 0.0));
+    }
+
+    #[cfg(feature = "sf3-parallel")]
+    #[test]
+    fn import_samples_parallel() {
+        use super::{import_samples, import_samples_parallel, SampleData, SampleSource};
+
+        let mut file = std::fs::File::open("../testdata/Boomwhacker.sf3").unwrap();
+        let sf2 = SoundFont::load_sf2(&mut file).unwrap();
+        let (smpl, sm24) = SoundFont::sample_chunks(&sf2);
+        let source = SampleSource::Loaded(SampleData::load(&mut file, smpl, sm24).unwrap());
+
+        let options = Default::default();
+        let serial = import_samples(&sf2.sample_headers, &source, options).unwrap();
+        let parallel = import_samples_parallel(&sf2.sample_headers, &source, options).unwrap();
+
+        assert_eq!(serial.len(), parallel.len());
+        for (a, b) in serial.iter().zip(parallel.iter()) {
+            assert_eq!(a.name(), b.name());
+            assert_eq!(
+                (a.start(), a.end(), a.loop_start(), a.loop_end()),
+                (b.start(), b.end(), b.loop_start(), b.loop_end())
+            );
+            for i in a.start()..=a.end() {
+                assert_eq!(a.points().get(i as usize), b.points().get(i as usize));
+            }
+        }
+    }
+
+    #[test]
+    fn browse() {
+        use crate::GeneratorType;
+
+        let mut file = std::fs::File::open("../testdata/sin.sf2").unwrap();
+        let font = SoundFont::load(&mut file).unwrap();
+
+        let presets: Vec<_> = font.presets().collect();
+        assert_eq!(presets.len(), 1);
+        let preset = presets[0];
+        assert_eq!(preset.name(), "Sine Wave");
+        assert_eq!((preset.banknum(), preset.num()), (0, 0));
+        assert!(preset.global_zone().is_none());
+
+        let [zone] = preset.zones() else {
+            panic!("Expected a single preset zone");
+        };
+        assert_eq!(zone.key_range(), 0..=127);
+        assert_eq!(zone.generators().count(), 0);
+
+        let inst = zone.instrument().unwrap();
+        assert_eq!(inst.name(), "Sine Wave");
+
+        let zones: Vec<_> = inst
+            .zones()
+            .iter()
+            .map(|zone| (zone.key_range(), zone.vel_range()))
+            .collect();
+        assert_eq!(
+            zones,
+            [(21..=64, 0..=127), (65..=73, 0..=127), (74..=108, 0..=127)]
+        );
+
+        let zone = &inst.zones()[0];
+        assert_eq!(
+            zone.generators().collect::<Vec<_>>(),
+            [(GeneratorType::OverrideRootKey, 57.0)]
+        );
+        assert!(zone.modulators().is_empty());
+
+        let sample = zone.sample().unwrap();
+        assert_eq!(sample.name(), "Sine A2");
+        assert_eq!(sample.num_points(), 172);
+        assert_eq!(sample.loop_range(), 67..167);
+        assert_eq!(sample.sample_rate(), 22001);
+        assert_eq!((sample.origpitch(), sample.pitchadj()), (60, 0));
+    }
+
+    #[test]
+    fn builder() {
+        use crate::{
+            GeneratorType, InstrumentDescriptor, PresetDescriptor, SampleDescriptor,
+            SoundFontBuilder, ZoneDescriptor,
+        };
+        use soundfont::raw::GeneratorType as RawGeneratorType;
+
+        // Rebuild `sin.sf2` from its parsed contents
+        let mut file = std::fs::File::open("../testdata/sin.sf2").unwrap();
+        let sf2 = soundfont::SoundFont2::load(&mut file).unwrap();
+        let smpl = sf2.sample_data.smpl.unwrap().read(&mut file).unwrap();
+        let points: Vec<i16> = smpl
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect();
+
+        let zone = |zone: &soundfont::Zone| {
+            let mut desc = ZoneDescriptor {
+                modulators: zone.mod_list.clone(),
+                ..Default::default()
+            };
+            for gen in zone.gen_list.iter() {
+                match gen.ty.into_result().unwrap() {
+                    RawGeneratorType::KeyRange => {
+                        let range = gen.amount.as_range().unwrap();
+                        desc.key_range = range.low..=range.high;
+                    }
+                    RawGeneratorType::VelRange => {
+                        let range = gen.amount.as_range().unwrap();
+                        desc.vel_range = range.low..=range.high;
+                    }
+                    RawGeneratorType::Instrument | RawGeneratorType::SampleID => {}
+                    ty => desc.generators.push((
+                        GeneratorType::try_from(ty).unwrap(),
+                        *gen.amount.as_i16().unwrap(),
+                    )),
+                }
+            }
+            desc
+        };
+
+        let mut builder = SoundFontBuilder::new();
+
+        let samples: Vec<_> = sf2
+            .sample_headers
+            .iter()
+            .map(|header| {
+                builder.add_sample(SampleDescriptor {
+                    name: header.name.clone(),
+                    data: points[header.start as usize..header.end as usize].to_vec(),
+                    sample_rate: header.sample_rate,
+                    loop_range: header.loop_start - header.start..header.loop_end - header.start,
+                    origpitch: header.origpitch,
+                    pitchadj: header.pitchadj,
+                })
+            })
+            .collect();
+
+        let instruments: Vec<_> = sf2
+            .instruments
+            .iter()
+            .map(|inst| {
+                let (global, zones) = match inst.zones.split_first() {
+                    Some((first, rest)) if first.sample().is_none() => (Some(zone(first)), rest),
+                    _ => (None, &inst.zones[..]),
+                };
+                builder.add_instrument(InstrumentDescriptor {
+                    name: inst.header.name.clone(),
+                    global_zone: global,
+                    zones: zones
+                        .iter()
+                        .map(|z| (samples[*z.sample().unwrap() as usize], zone(z)))
+                        .collect(),
+                })
+            })
+            .collect();
+
+        for preset in sf2.presets.iter() {
+            let (global, zones) = match preset.zones.split_first() {
+                Some((first, rest)) if first.instrument().is_none() => (Some(zone(first)), rest),
+                _ => (None, &preset.zones[..]),
+            };
+            builder.add_preset(PresetDescriptor {
+                name: preset.header.name.clone(),
+                bank: preset.header.bank,
+                num: preset.header.preset as u8,
+                global_zone: global,
+                zones: zones
+                    .iter()
+                    .map(|z| (instruments[*z.instrument().unwrap() as usize], zone(z)))
+                    .collect(),
+            });
+        }
+
+        let built = builder.build().unwrap();
+        let loaded = SoundFont::load(&mut std::fs::File::open("../testdata/sin.sf2").unwrap());
+
+        let keys = [40, 69, 90];
+        let rendered = render_font(built, &keys);
+        assert!(rendered.iter().any(|s| *s != 0.0));
+        assert_eq!(rendered, render_font(loaded.unwrap(), &keys));
+
+        // Generators with their own field are rejected
+        let mut builder = SoundFontBuilder::new();
+        let sample = builder.add_sample(SampleDescriptor::new("Sample", vec![0; 100], 44100));
+        builder.add_instrument(InstrumentDescriptor {
+            name: "Instrument".into(),
+            global_zone: None,
+            zones: vec![(
+                sample,
+                ZoneDescriptor {
+                    generators: vec![(GeneratorType::KeyRange, 0)],
+                    ..Default::default()
+                },
+            )],
+        });
+        assert!(matches!(
+            builder.build(),
+            Err(crate::LoadError::InvalidFont { .. })
+        ));
+    }
+
+    #[test]
+    fn samples_by_index() {
+        use crate::{InstrumentDescriptor, PresetDescriptor, SampleDescriptor, SoundFontBuilder};
+
+        // Two samples with the same name, only the second one is heard
+        let mut builder = SoundFontBuilder::new();
+        builder.add_sample(SampleDescriptor::new("Sample", vec![0; 100], 44100));
+        let square = (0..100)
+            .map(|i| if i < 50 { 8000 } else { -8000 })
+            .collect();
+        let sample = builder.add_sample(SampleDescriptor::new("Sample", square, 44100));
+
+        let inst = builder.add_instrument(InstrumentDescriptor {
+            name: "Instrument".into(),
+            global_zone: None,
+            zones: vec![(sample, Default::default())],
+        });
+        builder.add_preset(PresetDescriptor {
+            name: "Preset".into(),
+            zones: vec![(inst, Default::default())],
+            ..Default::default()
+        });
+
+        let rendered = render_font(builder.build().unwrap(), &[60]);
+        assert!(rendered.iter().any(|s| *s != 0.0));
+    }
+
+    #[test]
+    fn sample_not_found() {
+        use soundfont::raw::{GeneratorAmount, GeneratorType};
+
+        let mut file = std::fs::File::open("../testdata/sin.sf2").unwrap();
+        let mut sf2 = soundfont::SoundFont2::load(&mut file).unwrap();
+        let smpl = sf2.sample_data.smpl.unwrap().read(&mut file).unwrap();
+
+        let sample_id = sf2.instruments[0]
+            .zones
+            .iter_mut()
+            .flat_map(|zone| zone.gen_list.iter_mut())
+            .find(|g| g.ty == GeneratorType::SampleID)
+            .unwrap();
+        sample_id.amount = GeneratorAmount::U16(99);
+
+        let mut data = Vec::new();
+        sf2.write(&mut data, &smpl, None).unwrap();
+
+        let Err(crate::LoadError::SampleNotFound { name }) =
+            SoundFont::load(&mut std::io::Cursor::new(&data))
+        else {
+            panic!("The zone should not find its sample");
+        };
+        assert_eq!(name, "#99");
+    }
+}
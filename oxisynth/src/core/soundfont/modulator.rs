@@ -9,17 +9,20 @@ use soundfont::raw::{
     ModulatorTransform, SourceDirection, SourcePolarity, SourceType,
 };
 
+/// Modulator of a preset or instrument zone
+///
+/// See also _SoundFont 2.01 specifications section 8.2
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Mod {
-    pub dest: GeneratorType,
-    pub amount: f64,
+    pub(crate) dest: GeneratorType,
+    pub(crate) amount: f64,
 
-    pub src: ModulatorSource,
-    pub src2: ModulatorSource,
+    pub(crate) src: ModulatorSource,
+    pub(crate) src2: ModulatorSource,
 }
 
 impl Mod {
-    pub const fn const_from(mod_src: &SFModulator) -> Self {
+    pub(crate) const fn const_from(mod_src: &SFModulator) -> Self {
         let mut amount = mod_src.amount as f64;
 
         let dest = mod_src.dest; // index of controlled generator
@@ -69,11 +72,27 @@ impl Default for Mod {
 }
 
 impl Mod {
-    pub fn get_dest(&self) -> GeneratorType {
+    /// Generator controlled by the modulator
+    pub fn dest(&self) -> GeneratorType {
         self.dest
     }
 
-    pub fn get_value(&self, chan: &Channel, voice: &Voice) -> f32 {
+    /// Amount applied to the generator, disabled modulators have an amount of 0
+    pub fn amount(&self) -> f64 {
+        self.amount
+    }
+
+    /// Primary source of the modulator
+    pub fn source(&self) -> ModulatorSource {
+        self.src
+    }
+
+    /// Source scaling the amount of the modulator
+    pub fn amount_source(&self) -> ModulatorSource {
+        self.src2
+    }
+
+    pub(crate) fn get_value(&self, chan: &Channel, voice: &Voice) -> f32 {
         // 'special treatment' for default controller
         //
         //  Reference: SF2.01 section 8.4.2
@@ -352,7 +371,7 @@ impl Mod {
         self.amount as f32 * v1 * v2
     }
 
-    pub fn test_identity(&self, mod2: &Mod) -> bool {
+    pub(crate) fn test_identity(&self, mod2: &Mod) -> bool {
         if self.dest != mod2.dest {
             return false;
         }
@@ -1,3 +1,5 @@
+use std::ops::RangeInclusive;
+
 use crate::error::LoadError;
 
 use super::generator::{GeneratorList, GeneratorType};
@@ -49,14 +51,30 @@ impl Preset {
         })
     }
 
-    pub(crate) fn global_zone(&self) -> Option<&PresetZone> {
+    /// Zone with the defaults of the other zones of the preset
+    pub fn global_zone(&self) -> Option<&PresetZone> {
         self.global_zone.as_ref()
     }
 
-    pub(crate) fn zones(&self) -> &[PresetZone] {
+    pub fn zones(&self) -> &[PresetZone] {
         &self.zones
     }
 
+    /// Read the samples of a lazily loaded preset into the cache
+    pub(crate) fn preload(&self) {
+        let samples = self
+            .zones
+            .iter()
+            .filter_map(|zone| zone.inst.as_ref())
+            .flat_map(|inst| inst.zones())
+            .filter_map(|zone| zone.sample.as_ref())
+            .filter(|sample| sample.is_lazy());
+
+        for sample in samples {
+            sample.loaded();
+        }
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -70,20 +88,21 @@ impl Preset {
     }
 }
 
+/// Zone of a [`Preset`], playing an instrument over a key and velocity range
 pub struct PresetZone {
     #[allow(dead_code)]
-    pub name: String,
-    pub inst: Option<Instrument>,
-    pub key_low: u8,
-    pub key_high: u8,
-    pub vel_low: u8,
-    pub vel_high: u8,
-    pub gen: GeneratorList,
-    pub mods: Vec<Mod>,
+    pub(crate) name: String,
+    pub(crate) inst: Option<Instrument>,
+    pub(crate) key_low: u8,
+    pub(crate) key_high: u8,
+    pub(crate) vel_low: u8,
+    pub(crate) vel_high: u8,
+    pub(crate) gen: GeneratorList,
+    pub(crate) mods: Vec<Mod>,
 }
 
 impl PresetZone {
-    pub fn import(
+    pub(crate) fn import(
         name: String,
         sf2: &soundfont::SoundFont2,
         zone: &soundfont::Zone,
@@ -127,7 +146,7 @@ impl PresetZone {
         }
 
         let inst = if let Some(id) = zone.instrument() {
-            let i = Instrument::import(sf2, &sf2.instruments[*id as usize], samples)?;
+            let i = Instrument::import(&sf2.instruments[*id as usize], samples)?;
             Some(i)
         } else {
             None
@@ -157,4 +176,28 @@ impl PresetZone {
             mods,
         })
     }
+
+    /// Instrument of the zone, `None` for the global zone
+    pub fn instrument(&self) -> Option<&Instrument> {
+        self.inst.as_ref()
+    }
+
+    pub fn key_range(&self) -> RangeInclusive<u8> {
+        self.key_low..=self.key_high.min(127)
+    }
+
+    pub fn vel_range(&self) -> RangeInclusive<u8> {
+        self.vel_low..=self.vel_high.min(127)
+    }
+
+    /// Generators set by the zone, with their amount
+    ///
+    /// The key and velocity ranges, and the instrument, are not included.
+    pub fn generators(&self) -> impl Iterator<Item = (GeneratorType, f64)> + '_ {
+        self.gen.set()
+    }
+
+    pub fn modulators(&self) -> &[Mod] {
+        &self.mods
+    }
 }
@@ -1,17 +1,38 @@
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use soundfont::raw::SampleLink;
 
 use crate::error::LoadError;
 
-use super::SampleData;
+use super::{BrokenSamples, SampleData, SamplePoints, SampleStore};
 
+/// Points read by the 7th-order interpolation on each side of the played one
+const INTERPOLATION_REACH: usize = 3;
+
+/// Where the points of a [`Sample`] are
+#[derive(Clone, Debug)]
+pub(crate) enum SampleSource {
+    Loaded(SampleData),
+    /// Read from the font file when a note is started, or when the preset is selected
+    Lazy {
+        store: Arc<SampleStore>,
+        /// Computed on the first load
+        noise_floor: Arc<OnceLock<f64>>,
+    },
+    /// Failed to decode, and skipped with [`BrokenSamples::Skip`]
+    #[cfg_attr(not(feature = "sf3"), allow(dead_code))]
+    Skipped,
+}
+
+/// Sample of a [`SoundFont`](crate::SoundFont), played by the zones of its instruments
 #[derive(Clone, Debug)]
-pub(crate) struct Sample {
+pub struct Sample {
     name: Arc<str>,
 
     start: u32,
     end: u32,
+    /// Number of points, `end` is exclusive in SF2 headers but inclusive once decoded
+    num_points: u32,
 
     loop_start: u32,
     loop_end: u32,
@@ -21,7 +42,7 @@ pub(crate) struct Sample {
 
     sample_rate: u32,
     sample_type: SampleLink,
-    data: SampleData,
+    source: SampleSource,
 
     /// The amplitude, that will lower the level of the sample's loop to
     /// the noise floor. Needed for note turnoff optimization, will be
@@ -30,49 +51,61 @@ pub(crate) struct Sample {
 }
 
 impl Sample {
-    pub fn import(
+    pub(crate) fn import(
         sample: &soundfont::raw::SampleHeader,
-        data: SampleData,
+        source: SampleSource,
+        #[cfg_attr(not(feature = "sf3"), allow(unused_variables))] broken_samples: BrokenSamples,
     ) -> Result<Sample, LoadError> {
         let mut sample = Sample {
             name: sample.name.clone().into(),
             start: sample.start,
             end: sample.end,
+            num_points: sample.end.saturating_sub(sample.start),
             loop_start: sample.loop_start,
             loop_end: sample.loop_end,
             sample_rate: sample.sample_rate,
             origpitch: sample.origpitch,
             pitchadj: sample.pitchadj,
             sample_type: sample.sample_type,
-            data,
+            source,
 
             amplitude_that_reaches_noise_floor: None,
         };
 
         #[cfg(feature = "sf3")]
         {
-            if sample.sample_type.is_vorbis() {
+            if let (true, SampleSource::Loaded(data)) =
+                (sample.sample_type.is_vorbis(), &sample.source)
+            {
                 let start = sample.start as usize;
                 let end = sample.end as usize;
 
-                let sample_data = sample.data.as_byte_slice();
-
-                use lewton::inside_ogg::OggStreamReader;
-                use std::io::Cursor;
-
-                let buf = Cursor::new(&sample_data[start..end]);
-
-                let mut reader = OggStreamReader::new(buf).unwrap();
-
-                let mut new = Vec::new();
-
-                while let Some(mut pck) = reader.read_dec_packet().unwrap() {
-                    new.append(&mut pck[0]);
-                }
+                let decoded = match decode_vorbis(data.as_byte_slice(), start..end) {
+                    Ok(decoded) => decoded,
+                    Err(reason) => match broken_samples {
+                        BrokenSamples::Fail => {
+                            return Err(LoadError::SampleDecode {
+                                name: sample.name.to_string(),
+                                reason,
+                            });
+                        }
+                        BrokenSamples::Skip => {
+                            log::warn!("Skipping sample {:?}: {reason}", sample.name);
+                            sample.source = SampleSource::Skipped;
+                            return Ok(sample);
+                        }
+                        BrokenSamples::Silence => {
+                            log::warn!("Silencing sample {:?}: {reason}", sample.name);
+                            // The loop is fixed up below
+                            vec![0; 64]
+                        }
+                    },
+                };
 
                 sample.start = 0;
-                sample.end = (new.len() - 1) as u32;
-                sample.data = SampleData::new(new.into());
+                sample.end = (decoded.len() - 1) as u32;
+                sample.num_points = decoded.len() as u32;
+                sample.source = SampleSource::Loaded(SampleData::new(decoded.into()));
 
                 // loop is fowled?? (cluck cluck :)
                 if sample.loop_end > sample.end
@@ -126,42 +159,92 @@ impl Sample {
         }
 
         if self.amplitude_that_reaches_noise_floor.is_none() {
-            let mut peak_max = 0;
-            let mut peak_min = 0;
-
-            // Scan the loop
-            for i in self.loop_start..self.loop_end {
-                let val = self.data[i as usize] as i32;
-                if val > peak_max {
-                    peak_max = val
-                } else if val < peak_min {
-                    peak_min = val
-                }
+            // Lazily loaded samples are scanned on their first load
+            if let SampleSource::Loaded(data) = &self.source {
+                self.amplitude_that_reaches_noise_floor = Some(self.noise_floor(data));
             }
+        }
+    }
 
-            // Determine the peak level
-            let peak = if peak_max > -peak_min {
-                peak_max
-            } else {
-                -peak_min
-            };
+    fn noise_floor(&self, data: &SampleData) -> f64 {
+        let points = data.points();
+        let range = data.range();
+
+        let mut peak_max = 0.0;
+        let mut peak_min = 0.0;
+
+        // Scan the loop
+        let loop_start = (self.loop_start as usize).max(range.start);
+        let loop_end = (self.loop_end as usize).min(range.end);
+        for i in loop_start..loop_end {
+            let val = points.get(i);
+            if val > peak_max {
+                peak_max = val
+            } else if val < peak_min {
+                peak_min = val
+            }
+        }
 
-            // Avoid division by zero
-            let peak = if peak == 0 { 1 } else { peak };
+        // Determine the peak level
+        let peak = if peak_max > -peak_min {
+            peak_max
+        } else {
+            -peak_min
+        };
+
+        // Avoid division by zero
+        let peak = if peak == 0.0 { 1.0 } else { peak };
+
+        // Calculate what factor will make the loop inaudible
+        // For example: Take a peak of 3277 (10 % of 32768).  The
+        // normalized amplitude is 0.1 (10 % of 32768).  An amplitude
+        // factor of 0.0001 (as opposed to the default 0.00001) will
+        // drop this sample to the noise floor.
+
+        // 16 bits => 96+4=100 dB dynamic range => 0.00001
+        let normalized_amplitude_during_loop = peak / 32768.0;
+        0.00003 / normalized_amplitude_during_loop as f64
+    }
 
-            // Calculate what factor will make the loop inaudible
-            // For example: Take a peak of 3277 (10 % of 32768).  The
-            // normalized amplitude is 0.1 (10 % of 32768).  An amplitude
-            // factor of 0.0001 (as opposed to the default 0.00001) will
-            // drop this sample to the noise floor.
+    pub(crate) fn is_lazy(&self) -> bool {
+        matches!(self.source, SampleSource::Lazy { .. })
+    }
 
-            // 16 bits => 96+4=100 dB dynamic range => 0.00001
-            let normalized_amplitude_during_loop = peak as f32 / 32768.0;
-            let result = 0.00003 / normalized_amplitude_during_loop as f64;
+    /// The sample with its points loaded, reading them from the font file if needed.
+    ///
+    /// Returns `None` if they could not be read, or if the sample was skipped.
+    pub(crate) fn loaded(&self) -> Option<Sample> {
+        let (store, noise_floor) = match &self.source {
+            SampleSource::Loaded(_) => return Some(self.clone()),
+            SampleSource::Lazy { store, noise_floor } => (store, noise_floor),
+            SampleSource::Skipped => return None,
+        };
 
-            // Store in sample
-            self.amplitude_that_reaches_noise_floor = Some(result);
+        // 'end' is the last valid point, the loop end can be one past it
+        let points = self.start as usize..self.end as usize + 2;
+        if points.start > points.end || self.end as usize >= store.len() {
+            log::error!("Sample {:?} is outside of the sample data", self.name);
+            return None;
         }
+        // The interpolators also read the points around the sample and its loop
+        let points = points.start.saturating_sub(INTERPOLATION_REACH)
+            ..(points.end + INTERPOLATION_REACH).min(store.len());
+
+        let data = match store.load(points) {
+            Ok(data) => data,
+            Err(err) => {
+                log::error!("Failed to load sample {:?}: {err}", self.name);
+                return None;
+            }
+        };
+
+        let noise_floor = *noise_floor.get_or_init(|| self.noise_floor(&data));
+
+        Some(Sample {
+            source: SampleSource::Loaded(data),
+            amplitude_that_reaches_noise_floor: Some(noise_floor),
+            ..self.clone()
+        })
     }
 
     #[inline(always)]
@@ -170,35 +253,48 @@ impl Sample {
     }
 
     #[inline(always)]
-    pub fn start(&self) -> u32 {
+    pub(crate) fn start(&self) -> u32 {
         self.start
     }
 
     #[inline(always)]
-    pub fn end(&self) -> u32 {
+    pub(crate) fn end(&self) -> u32 {
         self.end
     }
 
     #[inline(always)]
-    pub fn amplitude_that_reaches_noise_floor(&self) -> Option<f64> {
+    pub(crate) fn amplitude_that_reaches_noise_floor(&self) -> Option<f64> {
         self.amplitude_that_reaches_noise_floor
     }
 
     #[inline(always)]
-    pub fn loop_start(&self) -> u32 {
+    pub(crate) fn loop_start(&self) -> u32 {
         self.loop_start
     }
 
     #[inline(always)]
-    pub fn loop_end(&self) -> u32 {
+    pub(crate) fn loop_end(&self) -> u32 {
         self.loop_end
     }
 
+    /// Number of points of the sample
+    pub fn num_points(&self) -> u32 {
+        self.num_points
+    }
+
+    /// Loop points, relative to the first point of the sample, within its points
+    pub fn loop_range(&self) -> std::ops::Range<u32> {
+        let point = |index: u32| index.saturating_sub(self.start).min(self.num_points);
+        point(self.loop_start)..point(self.loop_end)
+    }
+
+    /// MIDI key number at which the sample plays at its original pitch
     #[inline(always)]
     pub fn origpitch(&self) -> u8 {
         self.origpitch
     }
 
+    /// Pitch correction in cents
     #[inline(always)]
     pub fn pitchadj(&self) -> i8 {
         self.pitchadj
@@ -210,12 +306,43 @@ impl Sample {
     }
 
     #[inline(always)]
-    pub fn sample_type(&self) -> SampleLink {
+    pub(crate) fn sample_type(&self) -> SampleLink {
         self.sample_type
     }
 
     #[inline(always)]
-    pub fn data(&self) -> &[i16] {
-        &self.data
+    pub(crate) fn points(&self) -> SamplePoints<'_> {
+        match &self.source {
+            SampleSource::Loaded(data) => data.points(),
+            SampleSource::Lazy { .. } | SampleSource::Skipped => {
+                unreachable!("Voices play loaded samples")
+            }
+        }
     }
 }
+
+/// Decode the vorbis stream at `range` of the sample data bytes
+#[cfg(feature = "sf3")]
+fn decode_vorbis(data: &[u8], range: std::ops::Range<usize>) -> Result<Vec<i16>, String> {
+    use lewton::inside_ogg::OggStreamReader;
+    use std::io::Cursor;
+
+    let data = data
+        .get(range)
+        .ok_or_else(|| "compressed data is outside of the sample data".to_string())?;
+
+    let mut reader = OggStreamReader::new(Cursor::new(data)).map_err(|err| err.to_string())?;
+
+    let mut decoded = Vec::new();
+    while let Some(mut pck) = reader.read_dec_packet().map_err(|err| err.to_string())? {
+        if let Some(channel) = pck.first_mut() {
+            decoded.append(channel);
+        }
+    }
+
+    if decoded.is_empty() {
+        return Err("no audio data".to_string());
+    }
+
+    Ok(decoded)
+}
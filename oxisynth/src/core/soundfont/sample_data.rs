@@ -1,29 +1,75 @@
 use std::{
     io::{self, Read, Seek, SeekFrom},
+    ops::Range,
     sync::Arc,
 };
 
 use soundfont::raw::SampleChunk;
 
 #[derive(Debug, Clone)]
-pub(crate) struct SampleData(Arc<[i16]>);
+pub(crate) struct SampleData {
+    data: Arc<[i16]>,
+    /// Least significant bytes of 24-bit samples, from the sm24 chunk
+    sm24: Option<Arc<[u8]>>,
+    /// Index of the first point of `data` in the sample data of the font
+    offset: usize,
+}
 
 impl SampleData {
-    #[cfg_attr(not(feature = "sf3"), allow(dead_code))]
     pub fn new(data: Arc<[i16]>) -> Self {
-        Self(data)
+        Self {
+            data,
+            sm24: None,
+            offset: 0,
+        }
     }
 
-    pub fn load<F: Read + Seek>(file: &mut F, smpl: &SampleChunk) -> io::Result<Self> {
-        let sample_pos = smpl.offset;
-        let sample_size = smpl.len as usize;
+    /// Returns `sm24` if its size matches `smpl`, the chunk is ignored otherwise.
+    pub fn valid_sm24<'a>(
+        smpl: &SampleChunk,
+        sm24: Option<&'a SampleChunk>,
+    ) -> Option<&'a SampleChunk> {
+        let points = smpl.len / 2;
+
+        // The sm24 chunk has one byte per sample, padded to an even size
+        match sm24 {
+            Some(sm24) if sm24.len == points + points % 2 => Some(sm24),
+            Some(sm24) => {
+                log::warn!(
+                    "Ignoring sm24 chunk: size {} does not match the smpl chunk",
+                    sm24.len
+                );
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Load the 16-bit samples of `smpl`, and their low bytes from `sm24` if its size matches.
+    pub fn load<F: Read + Seek>(
+        file: &mut F,
+        smpl: &SampleChunk,
+        sm24: Option<&SampleChunk>,
+    ) -> io::Result<Self> {
+        let sm24 = Self::valid_sm24(smpl, sm24);
+        Self::load_range(file, smpl, sm24, 0..smpl.len as usize / 2)
+    }
+
+    /// Load the sample `points` of the font, `sm24` should be checked with [`Self::valid_sm24()`].
+    pub fn load_range<F: Read + Seek>(
+        file: &mut F,
+        smpl: &SampleChunk,
+        sm24: Option<&SampleChunk>,
+        points: Range<usize>,
+    ) -> io::Result<Self> {
+        let sample_pos = smpl.offset + points.start as u64 * 2;
 
         if let Err(err) = file.seek(SeekFrom::Start(sample_pos)) {
             log::error!("Failed to seek position in data file: {err}");
             return Err(err);
         }
 
-        let mut data = vec![0i16; sample_size / 2];
+        let mut data = vec![0i16; points.len()];
 
         {
             let byte_slice = crate::unsafe_stuff::slice_i16_to_u8_mut(&mut data);
@@ -42,20 +88,71 @@ impl SampleData {
             }
         }
 
-        Ok(Self(data.into()))
+        let sm24 = match sm24 {
+            Some(sm24) => {
+                let mut sm24_data = vec![0u8; points.len()];
+                file.seek(SeekFrom::Start(sm24.offset + points.start as u64))?;
+                file.read_exact(&mut sm24_data)?;
+                Some(sm24_data.into())
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            data: data.into(),
+            sm24,
+            offset: points.start,
+        })
     }
 
     #[cfg_attr(not(feature = "sf3"), allow(dead_code))]
     pub fn as_byte_slice(&self) -> &[u8] {
-        crate::unsafe_stuff::slice_i16_to_u8(&self.0)
+        crate::unsafe_stuff::slice_i16_to_u8(&self.data)
+    }
+
+    /// Indices of the points of the font in the data
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.data.len()
+    }
+
+    /// Size of the data in memory
+    pub fn byte_len(&self) -> usize {
+        std::mem::size_of_val(&*self.data) + self.sm24.as_ref().map_or(0, |sm24| sm24.len())
+    }
+
+    #[inline(always)]
+    pub fn points(&self) -> SamplePoints<'_> {
+        SamplePoints {
+            data: &self.data,
+            sm24: self.sm24.as_deref(),
+            offset: self.offset,
+        }
     }
 }
 
-impl std::ops::Deref for SampleData {
-    type Target = [i16];
+/// Sample points read by the interpolators, in the 16-bit range
+pub(crate) struct SamplePoints<'a> {
+    data: &'a [i16],
+    sm24: Option<&'a [u8]>,
+    offset: usize,
+}
 
+impl SamplePoints<'_> {
+    /// Sample point at `index` of the font data, the sm24 byte adds the fractional part.
+    ///
+    /// Points outside of the loaded range are silent.
     #[inline(always)]
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    pub fn get(&self, index: usize) -> f32 {
+        let Some(index) = index
+            .checked_sub(self.offset)
+            .filter(|index| *index < self.data.len())
+        else {
+            return 0.0;
+        };
+        let point = self.data[index] as f32;
+        match self.sm24 {
+            Some(sm24) => point + sm24[index] as f32 / 256.0,
+            None => point,
+        }
     }
 }
@@ -0,0 +1,117 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read, Seek},
+    ops::Range,
+    sync::Mutex,
+};
+
+use soundfont::raw::SampleChunk;
+
+use super::SampleData;
+
+pub(crate) trait ReadSeek: Read + Seek + Send {}
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// Sample data of a lazily loaded font, read from the font file on demand
+///
+/// The loaded samples are cached, the least recently used ones are dropped from the cache once
+/// it gets over its limit. Voices keep the data they play alive, even when it is dropped from
+/// the cache.
+pub(crate) struct SampleStore {
+    file: Mutex<Box<dyn ReadSeek>>,
+    smpl: SampleChunk,
+    sm24: Option<SampleChunk>,
+    cache: Mutex<SampleCache>,
+}
+
+struct CacheEntry {
+    data: SampleData,
+    last_used: u64,
+}
+
+struct SampleCache {
+    entries: HashMap<Range<usize>, CacheEntry>,
+    /// Bytes of sample data in the cache
+    len: usize,
+    limit: usize,
+    /// Incremented on every access, to find the least recently used entry
+    clock: u64,
+}
+
+impl SampleStore {
+    pub fn new(
+        file: Box<dyn ReadSeek>,
+        smpl: SampleChunk,
+        sm24: Option<SampleChunk>,
+        cache_limit: usize,
+    ) -> Self {
+        Self {
+            file: Mutex::new(file),
+            smpl,
+            sm24,
+            cache: Mutex::new(SampleCache {
+                entries: HashMap::new(),
+                len: 0,
+                limit: cache_limit,
+                clock: 0,
+            }),
+        }
+    }
+
+    /// Number of sample points in the font
+    pub fn len(&self) -> usize {
+        self.smpl.len as usize / 2
+    }
+
+    /// Sample data containing `points`, from the cache or read from the file
+    pub fn load(&self, points: Range<usize>) -> io::Result<SampleData> {
+        let mut cache = self.cache.lock().unwrap();
+        cache.clock += 1;
+        let clock = cache.clock;
+
+        if let Some(entry) = cache.entries.get_mut(&points) {
+            entry.last_used = clock;
+            return Ok(entry.data.clone());
+        }
+
+        let data = {
+            let mut file = self.file.lock().unwrap();
+            SampleData::load_range(&mut *file, &self.smpl, self.sm24.as_ref(), points.clone())?
+        };
+
+        let len = data.byte_len();
+        if len <= cache.limit {
+            while cache.len + len > cache.limit {
+                let (oldest, _) = cache
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .unwrap();
+                let oldest = oldest.clone();
+
+                let entry = cache.entries.remove(&oldest).unwrap();
+                cache.len -= entry.data.byte_len();
+            }
+
+            cache.len += len;
+            cache.entries.insert(
+                points,
+                CacheEntry {
+                    data: data.clone(),
+                    last_used: clock,
+                },
+            );
+        }
+
+        Ok(data)
+    }
+}
+
+impl std::fmt::Debug for SampleStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SampleStore")
+            .field("smpl", &self.smpl)
+            .field("sm24", &self.sm24)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,164 @@
+mod mts;
+
+use crate::OxiError;
+
+use super::voice_pool::MasterParams;
+use super::{midi, Core};
+
+const UNIVERSAL_NON_REALTIME: u8 = 0x7E;
+const UNIVERSAL_REALTIME: u8 = 0x7F;
+/// Device ID that addresses every device
+const ALL_CALL: u8 = 0x7F;
+
+/// Non-realtime sub-ID #1
+const GENERAL_MIDI: u8 = 0x09;
+/// Realtime sub-ID #1
+const DEVICE_CONTROL: u8 = 0x04;
+/// Realtime and non-realtime sub-ID #1
+const MIDI_TUNING_STANDARD: u8 = 0x08;
+
+/// Global state set with the universal realtime device control messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MasterState {
+    /// 14-bit, 0 is silence
+    pub volume: u16,
+    /// 14-bit, 0x2000 is center
+    pub balance: u16,
+    /// 14-bit, 0x2000 is A440, the range is -100..+100 cents
+    pub fine_tuning: u16,
+    /// 7-bit, 0x40 is A440, the range is -64..+63 semitones
+    pub coarse_tuning: u8,
+}
+
+impl Default for MasterState {
+    fn default() -> Self {
+        Self {
+            volume: 0x3FFF,
+            balance: 0x2000,
+            fine_tuning: 0x2000,
+            coarse_tuning: 0x40,
+        }
+    }
+}
+
+impl MasterState {
+    /// Linear factor applied to the synth gain
+    pub fn gain(&self) -> f32 {
+        self.volume as f32 / 0x3FFF as f32
+    }
+
+    pub fn params(&self) -> MasterParams {
+        let fine = (self.fine_tuning as f32 - 8192.0) / 8192.0 * 100.0;
+        let coarse = (self.coarse_tuning as f32 - 64.0) * 100.0;
+
+        MasterParams {
+            tuning: coarse + fine,
+            pan: (self.balance as f32 - 8192.0) / 8192.0 * 500.0,
+        }
+    }
+}
+
+/// Handle a SysEx message, with or without the `F0`/`F7` framing.
+///
+/// Messages that are not addressed to this device, or that are not supported, are ignored.
+pub(crate) fn handle_sysex(synth: &mut Core, data: &[u8]) -> Result<(), OxiError> {
+    let data = data.strip_prefix(&[0xF0]).unwrap_or(data);
+    let data = data.strip_suffix(&[0xF7]).unwrap_or(data);
+
+    let realtime = match *data {
+        [UNIVERSAL_NON_REALTIME, ..] => false,
+        [UNIVERSAL_REALTIME, ..] => true,
+        _ => {
+            log::trace!("Ignored SysEx: {data:02x?}");
+            return Ok(());
+        }
+    };
+
+    let [_, device_id, sub_id1, sub_id2, ref payload @ ..] = *data else {
+        return Ok(());
+    };
+
+    if !synth.accepts_device_id(device_id) {
+        return Ok(());
+    }
+
+    match (realtime, sub_id1, sub_id2, payload) {
+        // GM1 System On, GM2 System On
+        (false, GENERAL_MIDI, 0x01 | 0x03, _) => {
+            midi::system_reset(synth);
+        }
+        // GM System Off
+        (false, GENERAL_MIDI, 0x02, _) => {}
+        (true, DEVICE_CONTROL, 0x01..=0x04, &[lsb, msb, ..]) => {
+            let value = ((msb as u16 & 0x7F) << 7) | (lsb as u16 & 0x7F);
+
+            match sub_id2 {
+                0x01 => synth.master.volume = value,
+                0x02 => synth.master.balance = value,
+                0x03 => synth.master.fine_tuning = value,
+                // Only the MSB is used
+                _ => synth.master.coarse_tuning = msb & 0x7F,
+            }
+
+            synth.update_master();
+        }
+        (_, MIDI_TUNING_STANDARD, _, _) => {
+            mts::handle_mts(synth, realtime, sub_id2, payload, data);
+        }
+        _ => log::trace!("Unsupported universal SysEx: {data:02x?}"),
+    }
+
+    Ok(())
+}
+
+impl Core {
+    fn accepts_device_id(&self, device_id: u8) -> bool {
+        device_id == ALL_CALL || device_id == self.settings.device_id
+    }
+
+    /// Synth gain with the master volume applied
+    pub(crate) fn gain(&self) -> f32 {
+        self.settings.gain * self.master.gain()
+    }
+
+    /// Push the master state to the playing voices
+    pub(crate) fn update_master(&mut self) {
+        let gain = self.gain();
+        self.voices.set_gain(gain);
+        self.voices.set_master(self.master.params());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::MasterState;
+    use crate::{Synth, SynthDescriptor};
+
+    #[test]
+    fn master() {
+        let mut synth = Synth::new(SynthDescriptor::default()).unwrap();
+
+        // Master volume, half
+        synth
+            .send_sysex(&[0xF0, 0x7F, 0x7F, 0x04, 0x01, 0x00, 0x40, 0xF7])
+            .unwrap();
+        // Master coarse tuning, +2 semitones
+        synth
+            .send_sysex(&[0x7F, 0x7F, 0x04, 0x04, 0x00, 0x42])
+            .unwrap();
+        // Master fine tuning, addressed to an other device
+        synth
+            .send_sysex(&[0x7F, 0x01, 0x04, 0x03, 0x00, 0x50])
+            .unwrap();
+
+        let master = synth.core.master;
+        assert_eq!(master.volume, 0x2000);
+        assert_eq!(master.coarse_tuning, 0x42);
+        assert_eq!(master.fine_tuning, 0x2000);
+        assert_eq!(master.params().tuning, 200.0);
+
+        // GM System On
+        synth.send_sysex(&[0x7E, 0x7F, 0x09, 0x01]).unwrap();
+        assert_eq!(synth.core.master, MasterState::default());
+    }
+}
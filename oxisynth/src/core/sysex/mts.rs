@@ -0,0 +1,255 @@
+//! MIDI Tuning Standard messages
+
+use crate::core::{Core, ModulateCtrl};
+use crate::Tuning;
+
+/// Size of the tuning name in the dump messages
+const NAME_LEN: usize = 16;
+
+/// Handle the MTS message `sub_id2`.
+///
+/// `payload` are the bytes following the sub-IDs, `data` is the whole message and is used
+/// to verify the checksum of the dumps.
+pub(super) fn handle_mts(
+    synth: &mut Core,
+    realtime: bool,
+    sub_id2: u8,
+    payload: &[u8],
+    data: &[u8],
+) {
+    match (realtime, sub_id2, payload) {
+        // Bulk dump requests, there is no MIDI out to reply on
+        (false, 0x00 | 0x03, _) => {
+            log::trace!("Ignored MTS dump request: {data:02x?}");
+        }
+        // Bulk tuning dump
+        (false, 0x01, [program, ref dump @ ..]) => {
+            if checksum_ok(data) {
+                key_based_dump(synth, 0, *program, dump);
+            }
+        }
+        // Key-based tuning dump
+        (false, 0x04, [bank, program, ref dump @ ..]) => {
+            if checksum_ok(data) {
+                key_based_dump(synth, *bank, *program, dump);
+            }
+        }
+        // Scale/octave tuning dump, 1 byte format
+        (false, 0x05, [bank, program, ref dump @ ..]) => {
+            if checksum_ok(data) {
+                octave_dump(synth, *bank, *program, dump, 1);
+            }
+        }
+        // Scale/octave tuning dump, 2 byte format
+        (false, 0x06, [bank, program, ref dump @ ..]) => {
+            if checksum_ok(data) {
+                octave_dump(synth, *bank, *program, dump, 2);
+            }
+        }
+        // Single note tuning change
+        (true, 0x02, [program, _len, ref changes @ ..]) => {
+            note_change(synth, 0, *program, changes, true);
+        }
+        // Single note tuning change with bank select
+        (_, 0x07, [bank, program, _len, ref changes @ ..]) => {
+            note_change(synth, *bank, *program, changes, realtime);
+        }
+        // Scale/octave tuning, 1 byte format
+        (_, 0x08, [ff, gg, hh, ref values @ ..]) => {
+            octave_channels(synth, channel_mask(*ff, *gg, *hh), values, 1, realtime);
+        }
+        // Scale/octave tuning, 2 byte format
+        (_, 0x09, [ff, gg, hh, ref values @ ..]) => {
+            octave_channels(synth, channel_mask(*ff, *gg, *hh), values, 2, realtime);
+        }
+        _ => log::trace!("Unsupported MTS message: {data:02x?}"),
+    }
+}
+
+/// The checksum is the XOR of all bytes from the sub-ID (`7E`) up to the checksum itself
+fn checksum_ok(data: &[u8]) -> bool {
+    let Some((checksum, bytes)) = data.split_last() else {
+        return false;
+    };
+
+    let sum = bytes.iter().fold(0, |acc, b| acc ^ b) & 0x7F;
+    if sum != *checksum {
+        log::warn!("MTS dump with invalid checksum, ignored");
+        false
+    } else {
+        true
+    }
+}
+
+fn read_name(name: &[u8]) -> String {
+    String::from_utf8_lossy(name)
+        .trim_end_matches([' ', '\0'])
+        .to_owned()
+}
+
+/// Decode the 3 byte frequency format to cents, `None` means "no change"
+fn frequency([xx, yy, zz]: [u8; 3]) -> Option<f64> {
+    if [xx, yy, zz] == [0x7F; 3] {
+        return None;
+    }
+
+    let fraction = ((yy as u16 & 0x7F) << 7) | (zz as u16 & 0x7F);
+    Some((xx & 0x7F) as f64 * 100.0 + fraction as f64 * 100.0 / 16384.0)
+}
+
+/// Decode the 12 scale/octave offsets to cents
+fn octave_offsets(values: &[u8], bytes_per_value: usize) -> Option<[f64; 12]> {
+    let values = values.get(..12 * bytes_per_value)?;
+
+    let mut offsets = [0.0; 12];
+    for (offset, v) in offsets.iter_mut().zip(values.chunks_exact(bytes_per_value)) {
+        *offset = match *v {
+            // -64..+63 cents
+            [v] => (v & 0x7F) as f64 - 64.0,
+            // -100..+100 cents
+            [msb, lsb] => {
+                let v = ((msb as u16 & 0x7F) << 7) | (lsb as u16 & 0x7F);
+                (v as f64 - 8192.0) * 100.0 / 8192.0
+            }
+            _ => unreachable!(),
+        };
+    }
+
+    Some(offsets)
+}
+
+/// Channels 0 to 15 addressed by the 3 byte channel bitmask
+fn channel_mask(ff: u8, gg: u8, hh: u8) -> u16 {
+    ((ff as u16 & 0b11) << 14) | ((gg as u16 & 0x7F) << 7) | (hh as u16 & 0x7F)
+}
+
+fn key_based_dump(synth: &mut Core, bank: u8, program: u8, dump: &[u8]) {
+    let (Some(name), Some(keys)) = (dump.get(..NAME_LEN), dump.get(NAME_LEN..NAME_LEN + 128 * 3))
+    else {
+        log::warn!("MTS key-based tuning dump is too short, ignored");
+        return;
+    };
+
+    let mut tuning = synth
+        .tunings
+        .get(bank, program)
+        .map(|e| e.tuning)
+        .unwrap_or_default();
+
+    for (pitch, key) in tuning.pitch.iter_mut().zip(keys.chunks_exact(3)) {
+        if let Some(cents) = frequency([key[0], key[1], key[2]]) {
+            *pitch = cents;
+        }
+    }
+
+    synth.tunings.insert(bank, program, read_name(name), tuning);
+    synth.refresh_tuning(bank, program, false);
+}
+
+fn octave_dump(synth: &mut Core, bank: u8, program: u8, dump: &[u8], bytes_per_value: usize) {
+    let (Some(name), Some(offsets)) = (
+        dump.get(..NAME_LEN),
+        dump.get(NAME_LEN..)
+            .and_then(|v| octave_offsets(v, bytes_per_value)),
+    ) else {
+        log::warn!("MTS scale/octave tuning dump is too short, ignored");
+        return;
+    };
+
+    synth.tunings.insert(
+        bank,
+        program,
+        read_name(name),
+        Tuning::new_octave_tuning(&offsets),
+    );
+    synth.refresh_tuning(bank, program, false);
+}
+
+/// Realtime changes retune the notes that are playing, non-realtime ones apply to the next notes
+fn note_change(synth: &mut Core, bank: u8, program: u8, changes: &[u8], realtime: bool) {
+    let entry = synth.tunings.get_or_default(bank, program);
+
+    for change in changes.chunks_exact(4) {
+        let key = (change[0] & 0x7F) as usize;
+        if let Some(cents) = frequency([change[1], change[2], change[3]]) {
+            entry.tuning.pitch[key] = cents;
+        }
+    }
+
+    synth.refresh_tuning(bank, program, realtime);
+}
+
+fn octave_channels(
+    synth: &mut Core,
+    mask: u16,
+    values: &[u8],
+    bytes_per_value: usize,
+    realtime: bool,
+) {
+    let Some(offsets) = octave_offsets(values, bytes_per_value) else {
+        log::warn!("MTS scale/octave tuning message is too short, ignored");
+        return;
+    };
+
+    let tuning = Tuning::new_octave_tuning(&offsets);
+
+    for channel in synth
+        .channels
+        .iter_mut()
+        .take(16)
+        .filter(|ch| mask & (1 << ch.id()) != 0)
+    {
+        channel.set_tuning(Some(tuning));
+
+        if realtime {
+            synth.voices.modulate_voices(channel, ModulateCtrl::Tuning);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Synth, SynthDescriptor};
+
+    fn with_checksum(mut data: Vec<u8>) -> Vec<u8> {
+        let sum = data.iter().fold(0, |acc, b| acc ^ b) & 0x7F;
+        data.push(sum);
+        data
+    }
+
+    #[test]
+    fn bulk_dump_and_note_change() {
+        let mut synth = Synth::new(SynthDescriptor::default()).unwrap();
+
+        // Key-based tuning dump into bank 1, program 2: every key a quarter tone up
+        let mut dump = vec![0x7E, 0x7F, 0x08, 0x04, 1, 2];
+        dump.extend_from_slice(b"Quarter up      ");
+        for key in 0..128 {
+            dump.extend_from_slice(&[key, 0x40, 0x00]);
+        }
+        synth.send_sysex(&with_checksum(dump)).unwrap();
+
+        let entry = synth.core.tunings.get(1, 2).unwrap();
+        assert_eq!(entry.name, "Quarter up");
+        assert_eq!(entry.tuning.pitch[60], 6050.0);
+
+        synth.core.channels[3].select_tuning(1, 2, Some(entry.tuning));
+
+        // Realtime single note change of key 60 to 61 semitones, the channel follows
+        synth
+            .send_sysex(&[0x7F, 0x7F, 0x08, 0x07, 1, 2, 1, 60, 61, 0, 0])
+            .unwrap();
+        let tuning = synth.core.channels[3].tuning().unwrap();
+        assert_eq!(tuning.pitch[60], 6100.0);
+        assert_eq!(tuning.pitch[61], 6150.0);
+
+        // Scale/octave tuning, 1 byte format, on channel 0
+        let mut octave = vec![0x7F, 0x7F, 0x08, 0x08, 0, 0, 1];
+        octave.extend_from_slice(&[0x40 - 10; 12]);
+        synth.send_sysex(&octave).unwrap();
+
+        let tuning = synth.core.channels[0].tuning().unwrap();
+        assert_eq!(tuning.pitch[69], 6890.0);
+        assert!(synth.core.channels[1].tuning().is_none());
+    }
+}
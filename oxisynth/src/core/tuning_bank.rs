@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+
+use crate::Tuning;
+
+pub(crate) struct TuningEntry {
+    pub name: String,
+    pub tuning: Tuning,
+}
+
+/// Synth-wide table of tunings, addressed by bank and program
+#[derive(Default)]
+pub(crate) struct TuningBank {
+    tunings: BTreeMap<(u8, u8), TuningEntry>,
+}
+
+impl TuningBank {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, bank: u8, program: u8) -> Option<&TuningEntry> {
+        self.tunings.get(&(bank, program))
+    }
+
+    /// Add or replace a tuning
+    pub fn insert(&mut self, bank: u8, program: u8, name: String, tuning: Tuning) {
+        self.tunings
+            .insert((bank, program), TuningEntry { name, tuning });
+    }
+
+    pub fn remove(&mut self, bank: u8, program: u8) -> Option<TuningEntry> {
+        self.tunings.remove(&(bank, program))
+    }
+
+    /// Iterate over the tunings, ordered by bank and program
+    pub fn iter(&self) -> impl Iterator<Item = ((u8, u8), &TuningEntry)> {
+        self.tunings.iter().map(|(id, entry)| (*id, entry))
+    }
+
+    /// Get a tuning for modification, an equal tempered one is created if it does not exist yet
+    pub fn get_or_default(&mut self, bank: u8, program: u8) -> &mut TuningEntry {
+        self.tunings
+            .entry((bank, program))
+            .or_insert_with(|| TuningEntry {
+                name: String::new(),
+                tuning: Tuning::new(),
+            })
+    }
+}
@@ -1,7 +1,9 @@
 mod voice;
 
 use soundfont::raw::GeneralPalette;
-pub(crate) use voice::{EnvelopeStep, ModulateCtrl, Voice, VoiceAddMode, VoiceDescriptor};
+pub(crate) use voice::{
+    EnvelopeStep, MasterParams, ModulateCtrl, SoftPedalParams, Voice, VoiceAddMode, VoiceDescriptor,
+};
 
 use super::channel_pool::Channel;
 use super::soundfont::generator::GeneratorType;
@@ -12,18 +14,36 @@ struct VoiceId(pub(crate) usize);
 pub struct VoicePool {
     voices: Vec<Voice>,
     sample_rate: f32,
+    block_size: usize,
     polyphony_limit: usize,
+    master: MasterParams,
+    soft_pedal: SoftPedalParams,
+    /// Release time increase of the voices released while Hold2 is down, in timecents
+    hold2_release: f32,
+    /// Offset within the next block of the notes that are started
+    start_delay: usize,
 
     noteid: usize,
     storeid: usize,
 }
 
 impl VoicePool {
-    pub fn new(len: usize, sample_rate: f32) -> Self {
+    pub fn new(
+        len: usize,
+        sample_rate: f32,
+        block_size: usize,
+        soft_pedal: SoftPedalParams,
+        hold2_release: f32,
+    ) -> Self {
         Self {
             voices: Vec::new(),
             sample_rate,
+            block_size,
             polyphony_limit: len,
+            master: MasterParams::default(),
+            soft_pedal,
+            hold2_release,
+            start_delay: 0,
 
             noteid: 0,
             storeid: 0,
@@ -63,6 +83,19 @@ impl VoicePool {
         }
     }
 
+    /// Offset within the next block of the notes that are started from now on
+    pub fn set_start_delay(&mut self, start_delay: usize) {
+        self.start_delay = start_delay;
+    }
+
+    /// Set the global tuning and pan offsets, playing voices are updated right away
+    pub fn set_master(&mut self, master: MasterParams) {
+        self.master = master;
+        for voice in self.voices.iter_mut().filter(|v| v.is_playing()) {
+            voice.set_master(master);
+        }
+    }
+
     pub fn noteoff(&mut self, channel: &Channel, min_note_length_ticks: usize, key: u8) {
         for voice in self
             .voices
@@ -84,6 +117,21 @@ impl VoicePool {
         }
     }
 
+    /// Move the sounding voices of the channel to `key`, returns `false` if there are none
+    pub fn legato(&mut self, channel: &Channel, key: u8, portamento_key: Option<u8>) -> bool {
+        let mut found = false;
+        for voice in self
+            .voices
+            .iter_mut()
+            .filter(|v| v.is_on())
+            .filter(|v| v.channel_id() == channel.id())
+        {
+            voice.legato(channel, key, portamento_key);
+            found = true;
+        }
+        found
+    }
+
     pub fn all_notes_off(&mut self, channel: &Channel, min_note_length_ticks: usize) {
         for voice in self
             .voices
@@ -133,6 +181,33 @@ impl VoicePool {
         }
     }
 
+    /// Sostenuto pedal down: hold the notes whose key is down, once the key is released
+    pub fn sostenuto_on(&mut self, channel: &Channel) {
+        for voice in self
+            .voices
+            .iter_mut()
+            .filter(|v| v.channel_id() == channel.id())
+            .filter(|v| v.is_on())
+        {
+            voice.set_sostenuto(true);
+        }
+    }
+
+    /// Sostenuto pedal up: release the held notes, unless the damper pedal sustains them
+    pub fn sostenuto_off(&mut self, channel: &Channel, min_note_length_ticks: usize) {
+        for voice in self
+            .voices
+            .iter_mut()
+            .filter(|v| v.channel_id() == channel.id())
+        {
+            voice.set_sostenuto(false);
+
+            if voice.is_held_by_sostenuto() {
+                voice.noteoff(channel, min_note_length_ticks);
+            }
+        }
+    }
+
     pub fn modulate_voices(&mut self, channel: &Channel, ctrl: ModulateCtrl) {
         for voice in self
             .voices
@@ -165,7 +240,7 @@ impl VoicePool {
             if voice.channel_id() == 0xff {
                 this_voice_prio -= 2000.0;
             }
-            if voice.is_sustained() {
+            if voice.is_sustained() || voice.is_held_by_sostenuto() {
                 this_voice_prio -= 1000.0;
             }
             this_voice_prio -= noteid.wrapping_sub(voice.get_note_id()) as f32;
@@ -261,7 +336,13 @@ impl VoicePool {
 
         let voice_id = match voice_id {
             Some(id) => {
-                self.voices[id.0] = Voice::new(self.sample_rate, desc, self.storeid);
+                self.voices[id.0] = Voice::new(
+                    self.sample_rate,
+                    self.block_size,
+                    self.master,
+                    desc,
+                    self.storeid,
+                );
                 Some(id)
             }
             // If none free voice was found:
@@ -269,14 +350,25 @@ impl VoicePool {
                 // Check if we can add a new voice
                 if self.voices.len() < self.polyphony_limit {
                     // If we can we do...
-                    self.voices
-                        .push(Voice::new(self.sample_rate, desc, self.storeid));
+                    self.voices.push(Voice::new(
+                        self.sample_rate,
+                        self.block_size,
+                        self.master,
+                        desc,
+                        self.storeid,
+                    ));
                     Some(VoiceId(self.voices.len() - 1))
                 } else {
                     // If we can't we free already existing one...
                     let id = self.free_voice_by_kill(self.noteid);
                     if let Some(id) = id {
-                        self.voices[id.0] = Voice::new(self.sample_rate, desc, self.storeid);
+                        self.voices[id.0] = Voice::new(
+                            self.sample_rate,
+                            self.block_size,
+                            self.master,
+                            desc,
+                            self.storeid,
+                        );
                     }
                     id
                 }
@@ -286,6 +378,13 @@ impl VoicePool {
         if let Some(id) = voice_id {
             after(&mut self.voices[id.0]);
 
+            self.voices[id.0].set_start_delay(self.start_delay);
+            self.voices[id.0].set_hold2_release(self.hold2_release);
+
+            if channel.soft_pedal() {
+                self.voices[id.0].set_soft_pedal(self.soft_pedal);
+            }
+
             // add the synthesis process to the synthesis loop.
             self.start_voice(channel, id);
             Ok(())
@@ -115,12 +115,12 @@ impl Voice {
     /// efficient.
     pub fn dsp_float_interpolate_none(
         &mut self,
-        dsp_buf: &mut [f32; 64],
+        dsp_buf: &mut [f32],
         dsp_amp_incr: f32,
         phase_incr: f32,
     ) -> usize {
         let mut dsp_phase = self.phase;
-        let dsp_data = self.sample.data();
+        let dsp_data = self.sample.points();
         let mut dsp_amp = self.amp;
 
         // Convert playback "speed" floating point value to phase index/fract
@@ -138,8 +138,8 @@ impl Voice {
             let mut dsp_phase_index = ((dsp_phase + 0x80000000) >> 32) as usize;
 
             // interpolate sequence of sample points
-            while dsp_i < 64 && dsp_phase_index <= end_index {
-                dsp_buf[dsp_i] = dsp_amp * dsp_data[dsp_phase_index] as f32;
+            while dsp_i < dsp_buf.len() && dsp_phase_index <= end_index {
+                dsp_buf[dsp_i] = dsp_amp * dsp_data.get(dsp_phase_index);
 
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
@@ -159,7 +159,7 @@ impl Voice {
             }
 
             // break out if filled buffer
-            if dsp_i >= 64 {
+            if dsp_i >= dsp_buf.len() {
                 break;
             }
         }
@@ -175,12 +175,12 @@ impl Voice {
     /// smaller if end of sample occurs).
     pub fn dsp_float_interpolate_linear(
         &mut self,
-        dsp_buf: &mut [f32; 64],
+        dsp_buf: &mut [f32],
         dsp_amp_incr: f32,
         phase_incr: f32,
     ) -> usize {
         let mut dsp_phase = self.phase;
-        let dsp_data: &[i16] = self.sample.data();
+        let dsp_data = self.sample.points();
         let mut dsp_amp: f32 = self.amp;
 
         // Convert playback "speed" floating point value to phase index/fract
@@ -200,10 +200,10 @@ impl Voice {
         // 2nd interpolation point to use at end of loop or sample
         let point = if looping {
             // loop start
-            dsp_data[self.loopstart as usize]
+            dsp_data.get(self.loopstart as usize)
         } else {
             // duplicate end for samples no longer looping
-            dsp_data[self.end as usize]
+            dsp_data.get(self.end as usize)
         };
 
         let mut dsp_i: usize = 0;
@@ -211,13 +211,13 @@ impl Voice {
             let mut dsp_phase_index = (dsp_phase >> 32) as usize;
 
             // interpolate the sequence of sample points
-            while dsp_i < 64 && dsp_phase_index <= end_index {
+            while dsp_i < dsp_buf.len() && dsp_phase_index <= end_index {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.interp_coeff_linear[id];
 
                 dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * dsp_data[dsp_phase_index] as f32
-                        + coeffs[1] * dsp_data[dsp_phase_index + 1] as f32);
+                    * (coeffs[0] * dsp_data.get(dsp_phase_index)
+                        + coeffs[1] * dsp_data.get(dsp_phase_index + 1));
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
                 dsp_phase_index = (dsp_phase >> 32) as usize;
@@ -226,19 +226,19 @@ impl Voice {
             }
 
             // break out if buffer filled
-            if dsp_i >= 64 {
+            if dsp_i >= dsp_buf.len() {
                 break;
             }
             // we're now interpolating the last point
             end_index += 1;
 
             // interpolate within last point
-            while dsp_phase_index <= end_index && dsp_i < 64 {
+            while dsp_phase_index <= end_index && dsp_i < dsp_buf.len() {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.interp_coeff_linear[id];
 
-                dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * dsp_data[dsp_phase_index] as f32 + coeffs[1] * point as f32);
+                dsp_buf[dsp_i] =
+                    dsp_amp * (coeffs[0] * dsp_data.get(dsp_phase_index) + coeffs[1] * point);
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
                 dsp_phase_index = (dsp_phase >> 32) as usize;
@@ -259,7 +259,7 @@ impl Voice {
             }
 
             // break out if filled buffer
-            if dsp_i >= 64 {
+            if dsp_i >= dsp_buf.len() {
                 break;
             }
 
@@ -277,15 +277,15 @@ impl Voice {
     /// smaller if end of sample occurs).
     pub fn dsp_float_interpolate_4th_order(
         &mut self,
-        dsp_buf: &mut [f32; 64],
+        dsp_buf: &mut [f32],
         dsp_amp_incr: f32,
         phase_incr: f32,
     ) -> usize {
         let mut dsp_phase = self.phase;
-        let dsp_data: &[i16] = self.sample.data();
+        let dsp_data = self.sample.points();
         let mut dsp_amp: f32 = self.amp;
-        let end_point1: i16;
-        let end_point2: i16;
+        let end_point1: f32;
+        let end_point2: f32;
 
         // Convert playback "speed" floating point value to phase index/fract
         let dsp_phase_incr = phase_set_float(phase_incr);
@@ -302,25 +302,25 @@ impl Voice {
         } as usize;
 
         let mut start_index: usize;
-        let mut start_point: i16;
+        let mut start_point: f32;
 
         if self.has_looped {
             // set start_index and start point if looped or not
             start_index = self.loopstart as usize;
             // last point in loop (wrap around)
-            start_point = dsp_data[(self.loopend - 1) as usize];
+            start_point = dsp_data.get((self.loopend - 1) as usize);
         } else {
             start_index = self.start as usize;
             // just duplicate the point
-            start_point = dsp_data[self.start as usize];
+            start_point = dsp_data.get(self.start as usize);
         }
 
         // get points off the end (loop start if looping, duplicate point if end)
         if looping {
-            end_point1 = dsp_data[self.loopstart as usize];
-            end_point2 = dsp_data[self.loopstart as usize + 1];
+            end_point1 = dsp_data.get(self.loopstart as usize);
+            end_point2 = dsp_data.get(self.loopstart as usize + 1);
         } else {
-            end_point1 = dsp_data[self.end as usize];
+            end_point1 = dsp_data.get(self.end as usize);
             end_point2 = end_point1
         }
 
@@ -328,15 +328,15 @@ impl Voice {
         loop {
             let mut dsp_phase_index = (dsp_phase >> 32) as usize;
             // interpolate first sample point (start or loop start) if needed
-            while dsp_phase_index == start_index && dsp_i < 64 {
+            while dsp_phase_index == start_index && dsp_i < dsp_buf.len() {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.interp_coeff[id];
 
                 dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * start_point as f32
-                        + coeffs[1] * dsp_data[dsp_phase_index] as f32
-                        + coeffs[2] * dsp_data[dsp_phase_index + 1] as f32
-                        + coeffs[3] * dsp_data[dsp_phase_index + 2] as f32);
+                    * (coeffs[0] * start_point
+                        + coeffs[1] * dsp_data.get(dsp_phase_index)
+                        + coeffs[2] * dsp_data.get(dsp_phase_index + 1)
+                        + coeffs[3] * dsp_data.get(dsp_phase_index + 2));
 
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
@@ -346,15 +346,15 @@ impl Voice {
             }
 
             // interpolate the sequence of sample points
-            while dsp_i < 64 && dsp_phase_index <= end_index {
+            while dsp_i < dsp_buf.len() && dsp_phase_index <= end_index {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.interp_coeff[id];
 
                 dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * dsp_data[dsp_phase_index - 1] as f32
-                        + coeffs[1] * dsp_data[dsp_phase_index] as f32
-                        + coeffs[2] * dsp_data[dsp_phase_index + 1] as f32
-                        + coeffs[3] * dsp_data[dsp_phase_index + 2] as f32);
+                    * (coeffs[0] * dsp_data.get(dsp_phase_index - 1)
+                        + coeffs[1] * dsp_data.get(dsp_phase_index)
+                        + coeffs[2] * dsp_data.get(dsp_phase_index + 1)
+                        + coeffs[3] * dsp_data.get(dsp_phase_index + 2));
 
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
@@ -364,7 +364,7 @@ impl Voice {
             }
 
             // break out if buffer filled
-            if dsp_i >= 64 {
+            if dsp_i >= dsp_buf.len() {
                 break;
             }
 
@@ -372,15 +372,15 @@ impl Voice {
             end_index += 1;
 
             // interpolate within 2nd to last point
-            while dsp_phase_index <= end_index && dsp_i < 64 {
+            while dsp_phase_index <= end_index && dsp_i < dsp_buf.len() {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.interp_coeff[id];
 
                 dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * dsp_data[dsp_phase_index - 1] as f32
-                        + coeffs[1] * dsp_data[dsp_phase_index] as f32
-                        + coeffs[2] * dsp_data[dsp_phase_index + 1] as f32
-                        + coeffs[3] * end_point1 as f32);
+                    * (coeffs[0] * dsp_data.get(dsp_phase_index - 1)
+                        + coeffs[1] * dsp_data.get(dsp_phase_index)
+                        + coeffs[2] * dsp_data.get(dsp_phase_index + 1)
+                        + coeffs[3] * end_point1);
 
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
@@ -392,15 +392,15 @@ impl Voice {
             end_index += 1;
 
             // interpolate within the last point
-            while dsp_phase_index <= end_index && dsp_i < 64 {
+            while dsp_phase_index <= end_index && dsp_i < dsp_buf.len() {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.interp_coeff[id];
 
                 dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * dsp_data[dsp_phase_index - 1] as f32
-                        + coeffs[1] * dsp_data[dsp_phase_index] as f32
-                        + coeffs[2] * end_point1 as f32
-                        + coeffs[3] * end_point2 as f32);
+                    * (coeffs[0] * dsp_data.get(dsp_phase_index - 1)
+                        + coeffs[1] * dsp_data.get(dsp_phase_index)
+                        + coeffs[2] * end_point1
+                        + coeffs[3] * end_point2);
 
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
@@ -420,12 +420,12 @@ impl Voice {
                 if !self.has_looped {
                     self.has_looped = true;
                     start_index = self.loopstart as usize;
-                    start_point = dsp_data[(self.loopend - 1) as usize];
+                    start_point = dsp_data.get((self.loopend - 1) as usize);
                 }
             }
 
             // break out if filled buffer
-            if dsp_i >= 64 {
+            if dsp_i >= dsp_buf.len() {
                 break;
             }
 
@@ -440,11 +440,11 @@ impl Voice {
 
     pub fn dsp_float_interpolate_7th_order(
         &mut self,
-        dsp_buf: &mut [f32; 64],
+        dsp_buf: &mut [f32],
         dsp_amp_incr: f32,
         phase_incr: f32,
     ) -> usize {
-        let dsp_data: &[i16] = self.sample.data();
+        let dsp_data = self.sample.points();
         let mut dsp_amp: f32 = self.amp;
 
         // Convert playback "speed" floating point value to phase index/fract
@@ -463,31 +463,31 @@ impl Voice {
         let mut end_index = (end_index - 3) as usize;
 
         let mut start_index: usize;
-        let mut start_points: [i16; 3] = [0; 3];
-        let mut end_points: [i16; 3] = [0; 3];
+        let mut start_points: [f32; 3] = [0.0; 3];
+        let mut end_points: [f32; 3] = [0.0; 3];
 
         if self.has_looped {
             // set start_index and start point if looped or not
 
             start_index = self.loopstart as usize;
-            start_points[0] = dsp_data[(self.loopend - 1) as usize];
-            start_points[1] = dsp_data[(self.loopend - 2) as usize];
-            start_points[2] = dsp_data[(self.loopend - 3) as usize];
+            start_points[0] = dsp_data.get((self.loopend - 1) as usize);
+            start_points[1] = dsp_data.get((self.loopend - 2) as usize);
+            start_points[2] = dsp_data.get((self.loopend - 3) as usize);
         } else {
             start_index = self.start as usize;
             // just duplicate the start point
-            start_points[0] = dsp_data[self.start as usize];
+            start_points[0] = dsp_data.get(self.start as usize);
             start_points[1] = start_points[0];
             start_points[2] = start_points[0]
         }
 
         // get the 3 points off the end (loop start if looping, duplicate point if end)
         if looping {
-            end_points[0] = dsp_data[self.loopstart as usize];
-            end_points[1] = dsp_data[(self.loopstart + 1) as usize];
-            end_points[2] = dsp_data[(self.loopstart + 2) as usize];
+            end_points[0] = dsp_data.get(self.loopstart as usize);
+            end_points[1] = dsp_data.get((self.loopstart + 1) as usize);
+            end_points[2] = dsp_data.get((self.loopstart + 2) as usize);
         } else {
-            end_points[0] = dsp_data[self.end as usize];
+            end_points[0] = dsp_data.get(self.end as usize);
             end_points[1] = end_points[0];
             end_points[2] = end_points[0]
         }
@@ -498,17 +498,17 @@ impl Voice {
             dsp_phase_index = (dsp_phase >> 32) as usize;
 
             // interpolate first sample point (start or loop start) if needed
-            while dsp_phase_index == start_index && dsp_i < 64 {
+            while dsp_phase_index == start_index && dsp_i < dsp_buf.len() {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.sinc_table7[id];
                 dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * start_points[2] as f32
-                        + coeffs[1] * start_points[1] as f32
-                        + coeffs[2] * start_points[0] as f32
-                        + coeffs[3] * dsp_data[dsp_phase_index] as f32
-                        + coeffs[4] * dsp_data[dsp_phase_index + 1] as f32
-                        + coeffs[5] * dsp_data[dsp_phase_index + 2] as f32
-                        + coeffs[6] * dsp_data[dsp_phase_index + 3] as f32);
+                    * (coeffs[0] * start_points[2]
+                        + coeffs[1] * start_points[1]
+                        + coeffs[2] * start_points[0]
+                        + coeffs[3] * dsp_data.get(dsp_phase_index)
+                        + coeffs[4] * dsp_data.get(dsp_phase_index + 1)
+                        + coeffs[5] * dsp_data.get(dsp_phase_index + 2)
+                        + coeffs[6] * dsp_data.get(dsp_phase_index + 3));
 
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
@@ -519,17 +519,17 @@ impl Voice {
             start_index += 1;
 
             // interpolate 2nd to first sample point (start or loop start) if needed
-            while dsp_phase_index == start_index && dsp_i < 64 {
+            while dsp_phase_index == start_index && dsp_i < dsp_buf.len() {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.sinc_table7[id];
                 dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * start_points[1] as f32
-                        + coeffs[1] * start_points[0] as f32
-                        + coeffs[2] * dsp_data[dsp_phase_index - 1] as f32
-                        + coeffs[3] * dsp_data[dsp_phase_index] as f32
-                        + coeffs[4] * dsp_data[dsp_phase_index + 1] as f32
-                        + coeffs[5] * dsp_data[dsp_phase_index + 2] as f32
-                        + coeffs[6] * dsp_data[dsp_phase_index + 3] as f32);
+                    * (coeffs[0] * start_points[1]
+                        + coeffs[1] * start_points[0]
+                        + coeffs[2] * dsp_data.get(dsp_phase_index - 1)
+                        + coeffs[3] * dsp_data.get(dsp_phase_index)
+                        + coeffs[4] * dsp_data.get(dsp_phase_index + 1)
+                        + coeffs[5] * dsp_data.get(dsp_phase_index + 2)
+                        + coeffs[6] * dsp_data.get(dsp_phase_index + 3));
 
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
@@ -541,17 +541,17 @@ impl Voice {
             start_index += 1;
 
             // interpolate 3rd to first sample point (start or loop start) if needed
-            while dsp_phase_index == start_index && dsp_i < 64 {
+            while dsp_phase_index == start_index && dsp_i < dsp_buf.len() {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.sinc_table7[id];
                 dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * start_points[0] as f32
-                        + coeffs[1] * dsp_data[dsp_phase_index - 2] as f32
-                        + coeffs[2] * dsp_data[dsp_phase_index - 1] as f32
-                        + coeffs[3] * dsp_data[dsp_phase_index] as f32
-                        + coeffs[4] * dsp_data[dsp_phase_index + 1] as f32
-                        + coeffs[5] * dsp_data[dsp_phase_index + 2] as f32
-                        + coeffs[6] * dsp_data[dsp_phase_index + 3] as f32);
+                    * (coeffs[0] * start_points[0]
+                        + coeffs[1] * dsp_data.get(dsp_phase_index - 2)
+                        + coeffs[2] * dsp_data.get(dsp_phase_index - 1)
+                        + coeffs[3] * dsp_data.get(dsp_phase_index)
+                        + coeffs[4] * dsp_data.get(dsp_phase_index + 1)
+                        + coeffs[5] * dsp_data.get(dsp_phase_index + 2)
+                        + coeffs[6] * dsp_data.get(dsp_phase_index + 3));
 
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
@@ -564,17 +564,17 @@ impl Voice {
             start_index -= 2;
 
             // interpolate the sequence of sample points
-            while dsp_i < 64 && dsp_phase_index <= end_index {
+            while dsp_i < dsp_buf.len() && dsp_phase_index <= end_index {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.sinc_table7[id];
                 dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * dsp_data[dsp_phase_index - 3] as f32
-                        + coeffs[1] * dsp_data[dsp_phase_index - 2] as f32
-                        + coeffs[2] * dsp_data[dsp_phase_index - 1] as f32
-                        + coeffs[3] * dsp_data[dsp_phase_index] as f32
-                        + coeffs[4] * dsp_data[dsp_phase_index + 1] as f32
-                        + coeffs[5] * dsp_data[dsp_phase_index + 2] as f32
-                        + coeffs[6] * dsp_data[dsp_phase_index + 3] as f32);
+                    * (coeffs[0] * dsp_data.get(dsp_phase_index - 3)
+                        + coeffs[1] * dsp_data.get(dsp_phase_index - 2)
+                        + coeffs[2] * dsp_data.get(dsp_phase_index - 1)
+                        + coeffs[3] * dsp_data.get(dsp_phase_index)
+                        + coeffs[4] * dsp_data.get(dsp_phase_index + 1)
+                        + coeffs[5] * dsp_data.get(dsp_phase_index + 2)
+                        + coeffs[6] * dsp_data.get(dsp_phase_index + 3));
 
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
@@ -584,7 +584,7 @@ impl Voice {
             }
 
             // break out if buffer filled
-            if dsp_i >= 64 {
+            if dsp_i >= dsp_buf.len() {
                 break;
             }
 
@@ -592,17 +592,17 @@ impl Voice {
             end_index += 1;
 
             // interpolate within 3rd to last point
-            while dsp_phase_index <= end_index && dsp_i < 64 {
+            while dsp_phase_index <= end_index && dsp_i < dsp_buf.len() {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.sinc_table7[id];
                 dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * dsp_data[dsp_phase_index - 3] as f32
-                        + coeffs[1] * dsp_data[dsp_phase_index - 2] as f32
-                        + coeffs[2] * dsp_data[dsp_phase_index - 1] as f32
-                        + coeffs[3] * dsp_data[dsp_phase_index] as f32
-                        + coeffs[4] * dsp_data[dsp_phase_index + 1] as f32
-                        + coeffs[5] * dsp_data[dsp_phase_index + 2] as f32
-                        + coeffs[6] * end_points[0] as f32);
+                    * (coeffs[0] * dsp_data.get(dsp_phase_index - 3)
+                        + coeffs[1] * dsp_data.get(dsp_phase_index - 2)
+                        + coeffs[2] * dsp_data.get(dsp_phase_index - 1)
+                        + coeffs[3] * dsp_data.get(dsp_phase_index)
+                        + coeffs[4] * dsp_data.get(dsp_phase_index + 1)
+                        + coeffs[5] * dsp_data.get(dsp_phase_index + 2)
+                        + coeffs[6] * end_points[0]);
 
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
@@ -615,17 +615,17 @@ impl Voice {
             end_index += 1;
 
             // interpolate within 2nd to last point
-            while dsp_phase_index <= end_index && dsp_i < 64 {
+            while dsp_phase_index <= end_index && dsp_i < dsp_buf.len() {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.sinc_table7[id];
                 dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * dsp_data[dsp_phase_index - 3] as f32
-                        + coeffs[1] * dsp_data[dsp_phase_index - 2] as f32
-                        + coeffs[2] * dsp_data[dsp_phase_index - 1] as f32
-                        + coeffs[3] * dsp_data[dsp_phase_index] as f32
-                        + coeffs[4] * dsp_data[dsp_phase_index + 1] as f32
-                        + coeffs[5] * end_points[0] as f32
-                        + coeffs[6] * end_points[1] as f32);
+                    * (coeffs[0] * dsp_data.get(dsp_phase_index - 3)
+                        + coeffs[1] * dsp_data.get(dsp_phase_index - 2)
+                        + coeffs[2] * dsp_data.get(dsp_phase_index - 1)
+                        + coeffs[3] * dsp_data.get(dsp_phase_index)
+                        + coeffs[4] * dsp_data.get(dsp_phase_index + 1)
+                        + coeffs[5] * end_points[0]
+                        + coeffs[6] * end_points[1]);
 
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
@@ -638,17 +638,17 @@ impl Voice {
             end_index += 1;
 
             // interpolate within last point
-            while dsp_phase_index <= end_index && dsp_i < 64 {
+            while dsp_phase_index <= end_index && dsp_i < dsp_buf.len() {
                 let id = phase_fract_to_tablerow(dsp_phase as usize);
                 let coeffs = &DSP_FLOAT_GLOBAL.sinc_table7[id];
                 dsp_buf[dsp_i] = dsp_amp
-                    * (coeffs[0] * dsp_data[dsp_phase_index - 3] as f32
-                        + coeffs[1] * dsp_data[dsp_phase_index - 2] as f32
-                        + coeffs[2] * dsp_data[dsp_phase_index - 1] as f32
-                        + coeffs[3] * dsp_data[dsp_phase_index] as f32
-                        + coeffs[4] * end_points[0] as f32
-                        + coeffs[5] * end_points[1] as f32
-                        + coeffs[6] * end_points[2] as f32);
+                    * (coeffs[0] * dsp_data.get(dsp_phase_index - 3)
+                        + coeffs[1] * dsp_data.get(dsp_phase_index - 2)
+                        + coeffs[2] * dsp_data.get(dsp_phase_index - 1)
+                        + coeffs[3] * dsp_data.get(dsp_phase_index)
+                        + coeffs[4] * end_points[0]
+                        + coeffs[5] * end_points[1]
+                        + coeffs[6] * end_points[2]);
 
                 // increment phase and amplitude
                 dsp_phase += dsp_phase_incr;
@@ -669,14 +669,14 @@ impl Voice {
                 if !self.has_looped {
                     self.has_looped = true;
                     start_index = self.loopstart as usize;
-                    start_points[0] = dsp_data[(self.loopend - 1) as usize];
-                    start_points[1] = dsp_data[(self.loopend - 2) as usize];
-                    start_points[2] = dsp_data[(self.loopend - 3) as usize];
+                    start_points[0] = dsp_data.get((self.loopend - 1) as usize);
+                    start_points[1] = dsp_data.get((self.loopend - 2) as usize);
+                    start_points[2] = dsp_data.get((self.loopend - 3) as usize);
                 }
             }
 
             // break out if filled buffer
-            if dsp_i >= 64 {
+            if dsp_i >= dsp_buf.len() {
                 break;
             }
 
@@ -16,8 +16,7 @@ use crate::{
             modulator::Mod,
             Sample,
         },
-        write::FxBuf,
-        InterpolationMethod, BUFSIZE, BUFSIZE_F32,
+        InterpolationMethod,
     },
     midi_event::ControlFunction,
 };
@@ -50,6 +49,7 @@ enum VoiceStatus {
     Clean,
     On,
     Sustained,
+    HeldBySostenuto,
     Off,
 }
 
@@ -106,6 +106,8 @@ impl SampleMode {
 pub enum ModulateCtrl {
     CC(ControlFunction),
     SF(GeneralPalette),
+    /// The tuning of the channel changed
+    Tuning,
 }
 
 pub struct VoiceDescriptor<'a> {
@@ -115,6 +117,26 @@ pub struct VoiceDescriptor<'a> {
     pub vel: u8,
     pub start_time: usize,
     pub gain: f32,
+    /// Source key of the portamento glide
+    pub portamento_key: Option<u8>,
+}
+
+/// Global parameters that are applied on top of the channel generators of every voice
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct MasterParams {
+    /// Pitch offset in cents
+    pub tuning: f32,
+    /// Pan offset, in the units of the pan generator (-500..=500)
+    pub pan: f32,
+}
+
+/// Attenuation and filter cutoff reduction of the notes started with the soft pedal down
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct SoftPedalParams {
+    /// Attenuation in centibels
+    pub attenuation: f32,
+    /// Cutoff reduction in cents
+    pub cutoff: f32,
 }
 
 #[derive(Clone)]
@@ -156,6 +178,24 @@ pub(crate) struct Voice {
 
     gen: GeneratorList,
     synth_gain: f32,
+    master: MasterParams,
+
+    /// The key was down when the sostenuto pedal was pressed
+    sostenuto: bool,
+    soft_pedal: SoftPedalParams,
+    /// Released while Hold2 was down
+    hold2: bool,
+    /// Release time increase when released while Hold2 is down, in timecents
+    hold2_release: f32,
+    /// Silent samples at the start of the first block, for notes that start within a block
+    start_delay: usize,
+
+    /// Source key of the portamento, the glide is set up when the voice starts
+    portamento_key: Option<u8>,
+    /// Pitch offset of the portamento in cents, it reaches 0 after `portamento_count` blocks
+    portamento_offset: f32,
+    portamento_incr: f32,
+    portamento_count: u32,
 
     amplitude_that_reaches_noise_floor_nonloop: f32,
     amplitude_that_reaches_noise_floor_loop: f32,
@@ -208,6 +248,8 @@ pub(crate) struct Voice {
     mod_0: [Mod; 64],
 
     output_rate: f32,
+    /// Number of frames rendered by each `write`
+    block_size: usize,
 
     phase: Phase,
 
@@ -225,7 +267,13 @@ pub(crate) struct Voice {
 }
 
 impl Voice {
-    pub(super) fn new(output_rate: f32, desc: VoiceDescriptor, note_id: usize) -> Voice {
+    pub(super) fn new(
+        output_rate: f32,
+        block_size: usize,
+        master: MasterParams,
+        desc: VoiceDescriptor,
+        note_id: usize,
+    ) -> Voice {
         let mut volenv_data = Envelope::default();
 
         volenv_data[EnvelopeStep::Sustain] = EnvelopePortion {
@@ -306,6 +354,18 @@ impl Voice {
 
             gen: GeneratorList::new(desc.channel),
             synth_gain,
+            master,
+
+            sostenuto: false,
+            soft_pedal: SoftPedalParams::default(),
+            hold2: false,
+            hold2_release: 0.0,
+            start_delay: 0,
+
+            portamento_key: desc.portamento_key,
+            portamento_offset: 0.0,
+            portamento_incr: 0.0,
+            portamento_count: 0,
 
             amplitude_that_reaches_noise_floor_nonloop: 0.00003 / synth_gain,
             amplitude_that_reaches_noise_floor_loop: 0.00003 / synth_gain,
@@ -314,6 +374,7 @@ impl Voice {
             mod_0: [Mod::default(); 64],
             check_sample_sanity_flag: SampleSanity::empty(),
             output_rate,
+            block_size,
             phase: 0,
             pitch: 0.0,
             attenuation: 0.0,
@@ -468,6 +529,7 @@ impl Voice {
         // sample with its nominal loop settings. This happens, when the sample is used
         // for the first time.
         self.calculate_runtime_synthesis_parameters(channel);
+        self.start_portamento(channel);
 
         // Force setting of the phase at the first DSP loop run
         // This cannot be done earlier, because it depends on modulators.
@@ -475,6 +537,36 @@ impl Voice {
         self.status = VoiceStatus::On;
     }
 
+    /// Glide from the pitch of the portamento source key, in the portamento time of the channel
+    fn start_portamento(&mut self, channel: &Channel) {
+        let Some(from_key) = self.portamento_key else {
+            return;
+        };
+
+        let blocks =
+            (channel.portamento_time() as f32 * self.output_rate / 1000.0 / self.block_size as f32)
+                .round();
+
+        if blocks >= 1.0 {
+            self.portamento_offset =
+                (self.key_pitch(channel, from_key) - self.key_pitch(channel, self.key)) as f32;
+            self.portamento_incr = -self.portamento_offset / blocks;
+            self.portamento_count = blocks as u32;
+        }
+    }
+
+    /// Move the voice to an other key without retriggering it
+    pub(super) fn legato(&mut self, channel: &Channel, key: u8, portamento_key: Option<u8>) {
+        self.key = key;
+        self.portamento_key = portamento_key;
+        self.portamento_offset = 0.0;
+        self.portamento_count = 0;
+        self.start_portamento(channel);
+
+        self.gen[GeneratorType::Pitch].val = self.key_pitch(channel, key);
+        self.update_param(GeneratorType::Pitch);
+    }
+
     pub(super) fn noteoff(&mut self, channel: &Channel, min_note_length_ticks: usize) {
         if min_note_length_ticks > self.ticks {
             // Delay noteoff
@@ -488,9 +580,17 @@ impl Voice {
             channel.cc(SUSTAIN_SWITCH) >= 64
         };
 
-        if sustained {
+        if self.sostenuto && channel.sostenuto() {
+            self.status = VoiceStatus::HeldBySostenuto;
+        } else if sustained {
             self.status = VoiceStatus::Sustained;
         } else {
+            if channel.hold2() && !self.hold2 {
+                self.hold2 = true;
+                self.update_param(GeneratorType::VolEnvRelease);
+                self.update_param(GeneratorType::ModEnvRelease);
+            }
+
             if self.volenv_section == EnvelopeStep::Attack {
                 // A voice is turned off during the attack section of the volume
                 // envelope.  The attack section ramps up linearly with
@@ -515,6 +615,11 @@ impl Voice {
         let ctrl = match ctrl {
             ModulateCtrl::CC(control_function) => ControllerPalette::Midi(control_function as u8),
             ModulateCtrl::SF(v) => ControllerPalette::General(v),
+            ModulateCtrl::Tuning => {
+                self.gen[GeneratorType::Pitch].val = self.key_pitch(channel, self.key);
+                self.update_param(GeneratorType::Pitch);
+                return;
+            }
         };
 
         #[inline(always)]
@@ -527,7 +632,7 @@ impl Voice {
             let mod_0 = &mut self.mod_0[i];
 
             if mod_has_source(mod_0, ctrl) {
-                let gen = mod_0.get_dest();
+                let gen = mod_0.dest();
                 let mut modval = 0.0;
 
                 let mut k = 0;
@@ -546,7 +651,7 @@ impl Voice {
 
     pub(super) fn modulate_all(&mut self, channel: &Channel) {
         for i in 0..self.mod_count {
-            let gen = self.mod_0[i].get_dest();
+            let gen = self.mod_0[i].dest();
 
             let modval: f32 = self
                 .mod_0
@@ -676,16 +781,7 @@ impl Voice {
             dest_gen.mod_0 += modval as f64;
             i += 1
         }
-        let tuning = channel.tuning();
-        if let Some(tuning) = tuning {
-            self.gen[GeneratorType::Pitch].val = tuning.pitch[60]
-                + self.gen[GeneratorType::ScaleTune].val / 100.0f32 as f64
-                    * (tuning.pitch[self.key as usize] - tuning.pitch[60])
-        } else {
-            self.gen[GeneratorType::Pitch].val = self.gen[GeneratorType::ScaleTune].val
-                * (self.key as i32 as f32 - 60.0f32) as f64
-                + (100.0f32 * 60.0f32) as f64
-        }
+        self.gen[GeneratorType::Pitch].val = self.key_pitch(channel, self.key);
 
         for gen in list_of_generators_to_initialize.iter() {
             self.update_param(*gen);
@@ -694,6 +790,18 @@ impl Voice {
         self.min_attenuation_c_b = self.get_lower_boundary_for_attenuation(channel);
     }
 
+    /// Pitch of a key in cents, according to the tuning of the channel and the scale tuning
+    fn key_pitch(&self, channel: &Channel, key: u8) -> f64 {
+        if let Some(tuning) = channel.tuning() {
+            tuning.pitch[60]
+                + self.gen[GeneratorType::ScaleTune].val / 100.0f32 as f64
+                    * (tuning.pitch[key as usize] - tuning.pitch[60])
+        } else {
+            self.gen[GeneratorType::ScaleTune].val * (key as i32 as f32 - 60.0f32) as f64
+                + (100.0f32 * 60.0f32) as f64
+        }
+    }
+
     /// Make sure, that sample start / end point and loop points are in
     /// proper order. When starting up, calculate the initial phase.
     fn check_sample_sanity(&mut self) {
@@ -855,17 +963,20 @@ impl Voice {
         self.amp_chorus = self.chorus_send * gain / 32768.0;
     }
 
+    pub(super) fn set_master(&mut self, master: MasterParams) {
+        self.master = master;
+        self.update_param(GeneratorType::Pan);
+        self.update_param(GeneratorType::Pitch);
+    }
+
     pub(crate) fn write(
         &mut self,
         channel: &Channel,
         min_note_length_ticks: usize,
-        (dsp_left_buf, dsp_right_buf): (&mut [f32; 64], &mut [f32; 64]),
-        fx_left_buf: &mut FxBuf,
-        reverb_active: bool,
-        chorus_active: bool,
+        dsp_buf: &mut [f32],
+        (dsp_left_buf, dsp_right_buf): (&mut [f32], &mut [f32]),
+        (reverb_buf, chorus_buf): (Option<&mut [f32]>, Option<&mut [f32]>),
     ) {
-        let mut dsp_buf: [f32; 64] = [0.; 64];
-
         // make sure we're playing and that we have sample data
         if !self.is_playing() {
             return;
@@ -1022,17 +1133,25 @@ impl Voice {
             None
         };
 
+        // The first block of a note that starts within the block is shorter,
+        // the offset is kept until a block is actually rendered
+        let delay = self.start_delay;
+        let len = self.block_size - delay;
+
         if let Some(target_amp) = target_amplitude {
-            // Volume increment to go from voice->amp to target_amp in FLUID_BUFSIZE steps
-            let amp_incr = (target_amp - self.amp) / BUFSIZE_F32;
+            // Volume increment to go from voice->amp to target_amp in `len` steps
+            let amp_incr = (target_amp - self.amp) / len as f32;
             // no volume and not changing? - No need to process
             if !(self.amp == 0.0 && amp_incr == 0.0) {
+                self.start_delay = 0;
+
                 // Calculate the number of samples, that the DSP loop advances
                 // through the original waveform with each step in the output
                 // buffer. It is the ratio between the frequencies of original
                 // waveform and output waveform.
                 let mut phase_incr = ct2hz_real(
                     self.pitch
+                        + self.portamento_offset
                         + self.modlfo_val * self.modlfo_to_pitch
                         + self.viblfo_val * self.viblfo_to_pitch
                         + self.modenv_val * self.modenv_to_pitch,
@@ -1126,50 +1245,61 @@ impl Voice {
                         // buffer will sacrifice some performance, though.  Note: If
                         // the filter is still too 'grainy', then increase this number
                         // at will.
-                        self.a1_incr = (a1_temp - self.a1) / BUFSIZE_F32;
-                        self.a2_incr = (a2_temp - self.a2) / BUFSIZE_F32;
-                        self.b02_incr = (b02_temp - self.b02) / BUFSIZE_F32;
-                        self.b1_incr = (b1_temp - self.b1) / BUFSIZE_F32;
+                        self.a1_incr = (a1_temp - self.a1) / self.block_size as f32;
+                        self.a2_incr = (a2_temp - self.a2) / self.block_size as f32;
+                        self.b02_incr = (b02_temp - self.b02) / self.block_size as f32;
+                        self.b1_incr = (b1_temp - self.b1) / self.block_size as f32;
 
                         // Have to add the increments filter_coeff_incr_count times.
-                        self.filter_coeff_incr_count = BUFSIZE;
+                        self.filter_coeff_incr_count = self.block_size;
                     }
                     self.last_fres = fres
                 }
 
+                let dsp_buf = &mut dsp_buf[..len];
                 let count = match self.interp_method {
                     InterpolationMethod::None => {
-                        self.dsp_float_interpolate_none(&mut dsp_buf, amp_incr, phase_incr)
+                        self.dsp_float_interpolate_none(dsp_buf, amp_incr, phase_incr)
                     }
                     InterpolationMethod::Linear => {
-                        self.dsp_float_interpolate_linear(&mut dsp_buf, amp_incr, phase_incr)
+                        self.dsp_float_interpolate_linear(dsp_buf, amp_incr, phase_incr)
                     }
                     InterpolationMethod::FourthOrder => {
-                        self.dsp_float_interpolate_4th_order(&mut dsp_buf, amp_incr, phase_incr)
+                        self.dsp_float_interpolate_4th_order(dsp_buf, amp_incr, phase_incr)
                     }
                     InterpolationMethod::SeventhOrder => {
-                        self.dsp_float_interpolate_7th_order(&mut dsp_buf, amp_incr, phase_incr)
+                        self.dsp_float_interpolate_7th_order(dsp_buf, amp_incr, phase_incr)
                     }
                 };
 
                 if count > 0 {
+                    let fx_buf = (
+                        reverb_buf.map(|buf| &mut buf[delay..]),
+                        chorus_buf.map(|buf| &mut buf[delay..]),
+                    );
                     self.effects(
-                        &mut dsp_buf,
-                        count,
-                        (dsp_left_buf, dsp_right_buf),
-                        fx_left_buf,
-                        reverb_active,
-                        chorus_active,
+                        &mut dsp_buf[..count],
+                        (&mut dsp_left_buf[delay..], &mut dsp_right_buf[delay..]),
+                        fx_buf,
                     );
                 }
                 // turn off voice if short count (sample ended and not looping)
-                if count < BUFSIZE {
+                if count < len {
                     self.off();
                 }
             }
         }
 
-        self.ticks += BUFSIZE;
+        if self.portamento_count > 0 {
+            self.portamento_count -= 1;
+            self.portamento_offset = if self.portamento_count == 0 {
+                0.0
+            } else {
+                self.portamento_offset + self.portamento_incr
+            };
+        }
+
+        self.ticks += self.block_size;
     }
 
     /// Purpose:
@@ -1203,12 +1333,9 @@ impl Voice {
     #[inline]
     fn effects(
         &mut self,
-        dsp_buf: &mut [f32; 64],
-        count: usize,
+        dsp_buf: &mut [f32],
         (dsp_left_buf, dsp_right_buf): (&mut [f32], &mut [f32]),
-        fx_buf: &mut FxBuf,
-        reverb_active: bool,
-        chorus_active: bool,
+        (reverb_buf, chorus_buf): (Option<&mut [f32]>, Option<&mut [f32]>),
     ) {
         // IIR filter sample history
         let mut dsp_hist1 = self.hist1;
@@ -1236,7 +1363,7 @@ impl Voice {
 
         if dsp_filter_coeff_incr_count > 0 {
             // Increment is added to each filter coefficient filter_coeff_incr_count times.
-            for dsp in dsp_buf.iter_mut().take(count) {
+            for dsp in dsp_buf.iter_mut() {
                 // The filter is implemented in Direct-II form.
                 dsp_centernode = *dsp - dsp_a1 * dsp_hist1 - dsp_a2 * dsp_hist2;
                 *dsp = dsp_b02 * (dsp_centernode + dsp_hist2) + dsp_b1 * dsp_hist1;
@@ -1256,7 +1383,7 @@ impl Voice {
         // The filter parameters are constant.  This is duplicated to save time.
         else {
             // The filter is implemented in Direct-II form.
-            for dsp in dsp_buf.iter_mut().take(count) {
+            for dsp in dsp_buf.iter_mut() {
                 dsp_centernode = *dsp - dsp_a1 * dsp_hist1 - dsp_a2 * dsp_hist2;
                 *dsp = dsp_b02 * (dsp_centernode + dsp_hist2) + dsp_b1 * dsp_hist1;
                 dsp_hist2 = dsp_hist1;
@@ -1274,7 +1401,6 @@ impl Voice {
                 .iter_mut()
                 .zip(dsp_right_buf.iter_mut())
                 .zip(dsp_buf.iter().copied())
-                .take(count)
             {
                 // The voice is centered. Use voice->amp_left twice.
                 let v = self.amp_left * dsp;
@@ -1285,43 +1411,25 @@ impl Voice {
         // The voice is not centered. Stereo samples have one side zero.
         else {
             if self.amp_left != 0.0 {
-                for (left, dsp) in dsp_left_buf
-                    .iter_mut()
-                    .zip(dsp_buf.iter().copied())
-                    .take(count)
-                {
+                for (left, dsp) in dsp_left_buf.iter_mut().zip(dsp_buf.iter().copied()) {
                     *left += self.amp_left * dsp;
                 }
             }
             if self.amp_right != 0.0 {
-                for (right, dsp) in dsp_right_buf
-                    .iter_mut()
-                    .zip(dsp_buf.iter().copied())
-                    .take(count)
-                {
+                for (right, dsp) in dsp_right_buf.iter_mut().zip(dsp_buf.iter().copied()) {
                     *right += self.amp_right * dsp;
                 }
             }
         }
 
-        if reverb_active && self.amp_reverb != 0.0 {
-            for (fx, dsp) in fx_buf
-                .reverb
-                .iter_mut()
-                .zip(dsp_buf.iter().copied())
-                .take(count)
-            {
+        if let Some(reverb_buf) = reverb_buf.filter(|_| self.amp_reverb != 0.0) {
+            for (fx, dsp) in reverb_buf.iter_mut().zip(dsp_buf.iter().copied()) {
                 *fx += self.amp_reverb * dsp;
             }
         }
 
-        if chorus_active && self.amp_chorus != 0.0 {
-            for (fx, dsp) in fx_buf
-                .chorus
-                .iter_mut()
-                .zip(dsp_buf.iter().copied())
-                .take(count)
-            {
+        if let Some(chorus_buf) = chorus_buf.filter(|_| self.amp_chorus != 0.0) {
+            for (fx, dsp) in chorus_buf.iter_mut().zip(dsp_buf.iter().copied()) {
                 *fx += self.amp_chorus * dsp;
             }
         }
@@ -1364,7 +1472,7 @@ impl Voice {
         }
         let seconds = tc2sec(timecents);
         // buffers
-        ((self.output_rate as f64 * seconds / BUFSIZE as f64) + 0.5) as i32
+        ((self.output_rate as f64 * seconds / self.block_size as f64) + 0.5) as i32
     }
 
     /// The value of a generator (gen) has changed.  (The different
@@ -1394,7 +1502,7 @@ impl Voice {
         match gen {
             GeneratorType::Pan => {
                 // range checking is done in the fluid_pan function
-                self.pan = gen_sum!(GeneratorType::Pan);
+                self.pan = gen_sum!(GeneratorType::Pan) + self.master.pan;
 
                 self.amp_left = pan(self.pan, 1) * self.synth_gain / 32768.0;
                 self.amp_right = pan(self.pan, 0) * self.synth_gain / 32768.0;
@@ -1407,7 +1515,8 @@ impl Voice {
                 self.attenuation =
                     (self.gen[GeneratorType::Attenuation].val * ALT_ATTENUATION_SCALE
                         + self.gen[GeneratorType::Attenuation].mod_0
-                        + self.gen[GeneratorType::Attenuation].nrpn) as f32;
+                        + self.gen[GeneratorType::Attenuation].nrpn) as f32
+                        + self.soft_pedal.attenuation;
 
                 // Range: SF2.01 section 8.1.3 # 48
                 // Motivation for range checking:
@@ -1421,7 +1530,8 @@ impl Voice {
 
                 self.pitch = gen_sum!(GeneratorType::Pitch)
                     + 100.0 * gen_sum!(GeneratorType::CoarseTune)
-                    + gen_sum!(GeneratorType::FineTune);
+                    + gen_sum!(GeneratorType::FineTune)
+                    + self.master.tuning;
             }
 
             GeneratorType::ReverbSend => {
@@ -1467,7 +1577,7 @@ impl Voice {
                 // modulation.  The allowed range is tested in the 'fluid_ct2hz'
                 // function [PH,20021214]
 
-                self.fres = gen_sum!(GeneratorType::FilterFc);
+                self.fres = gen_sum!(GeneratorType::FilterFc) - self.soft_pedal.cutoff;
                 // The synthesis loop will have to recalculate the filter
                 // coefficients.
                 self.last_fres = -1.0;
@@ -1547,7 +1657,7 @@ impl Voice {
                 let val = gen_sum!(GeneratorType::ModLfoFreq);
 
                 let val = val.clamp(-16000.0, 4500.0);
-                self.modlfo_incr = 4.0 * BUFSIZE_F32 * act2hz(val) / self.output_rate;
+                self.modlfo_incr = 4.0 * self.block_size as f32 * act2hz(val) / self.output_rate;
             }
 
             GeneratorType::VibLfoFreq => {
@@ -1558,7 +1668,7 @@ impl Voice {
                 let freq = gen_sum!(GeneratorType::VibLfoFreq);
 
                 let freq = freq.clamp(-16000.0, 4500.0);
-                self.viblfo_incr = 4.0 * BUFSIZE_F32 * act2hz(freq) / self.output_rate;
+                self.viblfo_incr = 4.0 * self.block_size as f32 * act2hz(freq) / self.output_rate;
             }
 
             GeneratorType::VibLfoDelay => {
@@ -1670,7 +1780,7 @@ impl Voice {
 
                 let val = val.clamp(-12000.0, 5000.0);
 
-                let count = (self.output_rate * tc2sec_delay(val) / BUFSIZE_F32) as u32;
+                let count = (self.output_rate * tc2sec_delay(val) / self.block_size as f32) as u32;
 
                 self.volenv_data[EnvelopeStep::Delay] = EnvelopePortion {
                     count,
@@ -1686,8 +1796,9 @@ impl Voice {
 
                 let val = val.clamp(-12000.0, 8000.0);
 
-                let count =
-                    1u32.wrapping_add((self.output_rate * tc2sec_attack(val) / BUFSIZE_F32) as u32);
+                let count = 1u32.wrapping_add(
+                    (self.output_rate * tc2sec_attack(val) / self.block_size as f32) as u32,
+                );
 
                 self.volenv_data[EnvelopeStep::Attack] = EnvelopePortion {
                     count,
@@ -1737,12 +1848,16 @@ impl Voice {
             }
 
             GeneratorType::VolEnvRelease => {
-                let val = gen_sum!(GeneratorType::VolEnvRelease);
+                let mut val = gen_sum!(GeneratorType::VolEnvRelease);
+                if self.hold2 {
+                    val += self.hold2_release;
+                }
 
                 let val = val.clamp(-7200.0, 8000.0);
 
-                let count = 1u32
-                    .wrapping_add((self.output_rate * tc2sec_release(val) / BUFSIZE_F32) as u32);
+                let count = 1u32.wrapping_add(
+                    (self.output_rate * tc2sec_release(val) / self.block_size as f32) as u32,
+                );
 
                 self.volenv_data[EnvelopeStep::Release] = EnvelopePortion {
                     count,
@@ -1759,7 +1874,7 @@ impl Voice {
                 let val = val.clamp(-12000.0, 5000.0);
 
                 self.modenv_data[EnvelopeStep::Delay] = EnvelopePortion {
-                    count: (self.output_rate * tc2sec_delay(val) / BUFSIZE_F32) as u32,
+                    count: (self.output_rate * tc2sec_delay(val) / self.block_size as f32) as u32,
                     coeff: 0.0,
                     incr: 0.0,
                     min: -1.0,
@@ -1772,8 +1887,9 @@ impl Voice {
 
                 let val = val.clamp(-12000.0, 8000.0);
 
-                let count =
-                    1u32.wrapping_add((self.output_rate * tc2sec_attack(val) / BUFSIZE_F32) as u32);
+                let count = 1u32.wrapping_add(
+                    (self.output_rate * tc2sec_attack(val) / self.block_size as f32) as u32,
+                );
 
                 self.modenv_data[EnvelopeStep::Attack] = EnvelopePortion {
                     count,
@@ -1822,12 +1938,16 @@ impl Voice {
             }
 
             GeneratorType::ModEnvRelease => {
-                let val = gen_sum!(GeneratorType::ModEnvRelease);
+                let mut val = gen_sum!(GeneratorType::ModEnvRelease);
+                if self.hold2 {
+                    val += self.hold2_release;
+                }
 
                 let val = val.clamp(-12000.0, 8000.0);
 
-                let count = 1u32
-                    .wrapping_add((self.output_rate * tc2sec_release(val) / BUFSIZE_F32) as u32);
+                let count = 1u32.wrapping_add(
+                    (self.output_rate * tc2sec_release(val) / self.block_size as f32) as u32,
+                );
 
                 self.modenv_data[EnvelopeStep::Release] = EnvelopePortion {
                     count,
@@ -1888,11 +2008,275 @@ impl Voice {
     }
 
     pub(crate) fn is_playing(&self) -> bool {
-        matches!(self.status, VoiceStatus::On | VoiceStatus::Sustained)
+        matches!(
+            self.status,
+            VoiceStatus::On | VoiceStatus::Sustained | VoiceStatus::HeldBySostenuto
+        )
     }
 
     #[inline(always)]
     pub(super) fn is_sustained(&self) -> bool {
         self.status == VoiceStatus::Sustained
     }
+
+    pub(super) fn is_held_by_sostenuto(&self) -> bool {
+        self.status == VoiceStatus::HeldBySostenuto
+    }
+
+    /// Must be set before the voice is started
+    pub(super) fn set_start_delay(&mut self, start_delay: usize) {
+        self.start_delay = start_delay.min(self.block_size - 1);
+    }
+
+    /// Must be set before the voice is started
+    pub(super) fn set_soft_pedal(&mut self, soft_pedal: SoftPedalParams) {
+        self.soft_pedal = soft_pedal;
+    }
+
+    /// Must be set before the voice is released
+    pub(super) fn set_hold2_release(&mut self, hold2_release: f32) {
+        self.hold2_release = hold2_release;
+    }
+
+    pub(super) fn set_sostenuto(&mut self, sostenuto: bool) {
+        self.sostenuto = sostenuto;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EnvelopeStep, Voice};
+    use crate::test_util::{cc, note_off, note_on, send, sin_synth};
+    use crate::{NotePriority, Synth, SynthDescriptor, Tuning};
+
+    fn pitches(synth: &mut Synth) -> Vec<f32> {
+        synth
+            .core
+            .voices
+            .iter_mut()
+            .filter(|v| v.is_playing())
+            .map(|v| v.pitch)
+            .collect()
+    }
+
+    #[test]
+    fn retune_playing_voices() {
+        let mut synth = sin_synth(SynthDescriptor::default());
+
+        send(&mut synth, note_on(69));
+        let before = pitches(&mut synth);
+        assert!(!before.is_empty());
+
+        let quarter_up = Tuning::new_octave_tuning(&[50.0; 12]);
+
+        // Only the next notes
+        synth.set_tuning(0, Some(quarter_up)).unwrap();
+        assert_eq!(pitches(&mut synth), before);
+
+        synth.set_tuning_applied(0, Some(quarter_up)).unwrap();
+        for (after, before) in pitches(&mut synth).iter().zip(before.iter()) {
+            assert_eq!(after - before, 50.0);
+        }
+
+        // MTS realtime single note change of key 69, in bank 0 program 0
+        synth.define_tuning(0, 0, "", Tuning::new());
+        synth.select_tuning_applied(0, 0, 0).unwrap();
+        synth
+            .send_sysex(&[0x7F, 0x7F, 0x08, 0x02, 0, 1, 69, 69, 0x20, 0])
+            .unwrap();
+        for (after, before) in pitches(&mut synth).iter().zip(before.iter()) {
+            assert_eq!(after - before, 25.0);
+        }
+    }
+
+    #[test]
+    fn portamento() {
+        let mut synth = sin_synth(SynthDescriptor::default());
+
+        // 128ms glide
+        send(&mut synth, cc(5, 1));
+        send(&mut synth, cc(65, 127));
+
+        send(&mut synth, note_on(60));
+        send(&mut synth, note_on(72));
+
+        let offset = |synth: &mut Synth, key| {
+            synth
+                .core
+                .voices
+                .iter_mut()
+                .find(|v| v.is_playing() && v.key() == key)
+                .map(|v| v.portamento_offset)
+                .unwrap()
+        };
+
+        assert_eq!(offset(&mut synth, 60), 0.0);
+        assert_eq!(offset(&mut synth, 72), -1200.0);
+
+        // Half way through
+        let mut samples = vec![0.0; 2 * 44 * 64];
+        synth.write(samples.as_mut_slice());
+        assert!((offset(&mut synth, 72) + 600.0).abs() < 1.0);
+
+        synth.write(samples.as_mut_slice());
+        assert_eq!(offset(&mut synth, 72), 0.0);
+
+        // The portamento control key is used by the next note only, even with portamento off
+        send(&mut synth, cc(65, 0));
+        send(&mut synth, cc(84, 62));
+        send(&mut synth, note_on(64));
+        send(&mut synth, note_on(67));
+        assert_eq!(offset(&mut synth, 64), -200.0);
+        assert_eq!(offset(&mut synth, 67), 0.0);
+    }
+
+    #[test]
+    fn sostenuto() {
+        let mut synth = sin_synth(SynthDescriptor {
+            min_note_length: 0,
+            ..Default::default()
+        });
+
+        let on = |synth: &mut Synth, key| {
+            synth
+                .core
+                .voices
+                .iter_mut()
+                .filter(|v| v.key() == key)
+                .all(|v| v.is_playing() && v.volenv_section != EnvelopeStep::Release)
+        };
+
+        send(&mut synth, note_on(60));
+        send(&mut synth, cc(66, 127));
+        send(&mut synth, note_on(64));
+
+        // Only the note held when the pedal went down keeps sounding
+        send(&mut synth, note_off(60));
+        send(&mut synth, note_off(64));
+        assert!(on(&mut synth, 60));
+        assert!(!on(&mut synth, 64));
+
+        // Pedal changes while it is down do not capture new notes
+        send(&mut synth, note_on(67));
+        send(&mut synth, cc(66, 100));
+        send(&mut synth, note_off(67));
+        assert!(!on(&mut synth, 67));
+
+        // The damper pedal takes over on release
+        send(&mut synth, cc(64, 127));
+        send(&mut synth, cc(66, 0));
+        assert!(on(&mut synth, 60));
+        send(&mut synth, cc(64, 0));
+        assert!(!on(&mut synth, 60));
+    }
+
+    #[test]
+    fn soft_pedal_and_hold2() {
+        let mut synth = sin_synth(SynthDescriptor {
+            min_note_length: 0,
+            hold2_release: 1200.0,
+            ..Default::default()
+        });
+
+        let voice = |synth: &mut Synth, key| {
+            synth
+                .core
+                .voices
+                .iter_mut()
+                .find(|v| v.is_playing() && v.key() == key)
+                .cloned()
+                .unwrap()
+        };
+
+        send(&mut synth, note_on(60));
+        send(&mut synth, cc(67, 127));
+        send(&mut synth, note_on(64));
+
+        // Only the new note is softer and darker
+        let (loud, soft) = (voice(&mut synth, 60), voice(&mut synth, 64));
+        assert!((soft.attenuation - loud.attenuation - 60.0).abs() < 0.01);
+        assert_eq!(loud.fres - soft.fres, 1200.0);
+
+        // Hold2 lengthens the release by 1200 timecents, twice as long
+        send(&mut synth, note_off(60));
+        send(&mut synth, cc(69, 127));
+        send(&mut synth, note_off(64));
+        let release = |v: &Voice| v.volenv_data[EnvelopeStep::Release].count as f32;
+        let ratio = release(&voice(&mut synth, 64)) / release(&voice(&mut synth, 60));
+        assert!((ratio - 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn mono_legato() {
+        let mut synth = sin_synth(SynthDescriptor {
+            min_note_length: 0,
+            ..Default::default()
+        });
+
+        // Keys and note IDs of the voices that are not released
+        let sounding = |synth: &mut Synth| {
+            synth
+                .core
+                .voices
+                .iter_mut()
+                .filter(|v| v.is_on())
+                .map(|v| (v.key(), v.get_note_id()))
+                .collect::<Vec<_>>()
+        };
+
+        send(&mut synth, cc(126, 1));
+        send(&mut synth, note_on(60));
+        let [(60, first)] = sounding(&mut synth)[..] else {
+            panic!("one voice expected");
+        };
+
+        // Mono mode releases the sounding note and retriggers, also back on release
+        send(&mut synth, note_on(64));
+        let [(64, second)] = sounding(&mut synth)[..] else {
+            panic!("one voice expected");
+        };
+        assert_ne!(second, first);
+        send(&mut synth, note_off(64));
+        let [(60, third)] = sounding(&mut synth)[..] else {
+            panic!("one voice expected");
+        };
+        assert_ne!(third, second);
+        send(&mut synth, note_off(60));
+        assert!(sounding(&mut synth).is_empty());
+
+        // Legato moves the voice without retriggering it, and back on release
+        send(&mut synth, cc(68, 127));
+        send(&mut synth, note_on(60));
+        let [(60, note_id)] = sounding(&mut synth)[..] else {
+            panic!("one voice expected");
+        };
+        send(&mut synth, note_on(64));
+        assert_eq!(sounding(&mut synth), [(64, note_id)]);
+        send(&mut synth, note_off(64));
+        assert_eq!(sounding(&mut synth), [(60, note_id)]);
+        send(&mut synth, note_off(60));
+        assert!(sounding(&mut synth).is_empty());
+        send(&mut synth, cc(68, 0));
+
+        // A lower key does not take over with high-note priority
+        synth.set_note_priority(0, NotePriority::High).unwrap();
+        send(&mut synth, note_on(60));
+        send(&mut synth, note_on(55));
+        assert_eq!(sounding(&mut synth)[0].0, 60);
+        send(&mut synth, note_off(60));
+        assert_eq!(sounding(&mut synth)[0].0, 55);
+
+        // Poly mode turns the notes off
+        send(&mut synth, cc(127, 0));
+        assert!(sounding(&mut synth).is_empty());
+        send(&mut synth, note_on(60));
+        send(&mut synth, note_on(64));
+        assert_eq!(sounding(&mut synth).len(), 2);
+
+        // The legato footswitch makes the channel mono
+        send(&mut synth, cc(68, 127));
+        send(&mut synth, note_on(67));
+        assert_eq!(sounding(&mut synth).len(), 2);
+        assert!(sounding(&mut synth).iter().all(|(key, _)| *key == 67));
+    }
 }
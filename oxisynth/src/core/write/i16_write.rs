@@ -39,6 +39,10 @@ pub fn write_i16(
 ) {
     let mut di = synth.output.i16_output.dither_index;
 
+    if len != 0 {
+        synth.next_block(super::BlockOutput::Mixed);
+    }
+
     let mut cur = synth.output.cur;
     let mut i = 0;
     let mut j = loff;
@@ -46,8 +50,8 @@ pub fn write_i16(
 
     while i < len {
         // fill up the buffers as needed
-        if cur == 64 {
-            synth.one_block(false);
+        if cur == synth.output.block_size {
+            synth.one_block(super::BlockOutput::Mixed);
             cur = 0;
         }
 
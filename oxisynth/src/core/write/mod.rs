@@ -1,45 +1,73 @@
-use crate::core::Core;
+use crate::core::{midi, Core};
+use crate::{MidiEvent, Stems, StereoBuffer};
 
 #[cfg(feature = "i16-out")]
 mod i16_write;
 #[cfg(feature = "i16-out")]
 pub use i16_write::write_i16;
 
-use super::BUFSIZE;
-
 #[derive(Clone)]
 pub(crate) struct FxBuf {
-    pub reverb: [f32; 64],
-    pub chorus: [f32; 64],
+    pub reverb: Vec<f32>,
+    pub chorus: Vec<f32>,
+}
+
+impl FxBuf {
+    fn new(block_size: usize) -> Self {
+        Self {
+            reverb: vec![0.0; block_size],
+            chorus: vec![0.0; block_size],
+        }
+    }
+}
+
+/// Where `one_block` writes the voices and the effects returns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BlockOutput {
+    /// Audio groups, the effects returns are mixed into the first group
+    Mixed,
+    /// Audio groups, with separate effects returns
+    Groups,
+    /// One buffer pair per MIDI channel, with separate effects returns
+    Channels,
 }
 
 pub(crate) struct OutputBuffer {
-    left_buf: Vec<[f32; 64]>,
-    right_buf: Vec<[f32; 64]>,
+    left_buf: Vec<Vec<f32>>,
+    right_buf: Vec<Vec<f32>>,
+
+    /// Per MIDI channel buffers, only allocated for stems rendering
+    channel_left_buf: Vec<Vec<f32>>,
+    channel_right_buf: Vec<Vec<f32>>,
 
     fx_left_buf: FxBuf,
     fx_right_buf: FxBuf,
 
+    /// Scratch buffer of the voices
+    dsp_buf: Vec<f32>,
+
+    block_size: usize,
     cur: usize,
+    /// How the current block was rendered
+    block_output: BlockOutput,
 
     #[cfg(feature = "i16-out")]
     i16_output: i16_write::I16OutputState,
 }
 
 impl OutputBuffer {
-    pub(crate) fn new(nbuf: usize) -> Self {
+    pub(crate) fn new(nbuf: usize, block_size: usize) -> Self {
         Self {
-            left_buf: vec![[0.0; 64]; nbuf],
-            right_buf: vec![[0.0; 64]; nbuf],
-            fx_left_buf: FxBuf {
-                reverb: [0.0; 64],
-                chorus: [0.0; 64],
-            },
-            fx_right_buf: FxBuf {
-                reverb: [0.0; 64],
-                chorus: [0.0; 64],
-            },
-            cur: 64,
+            left_buf: vec![vec![0.0; block_size]; nbuf],
+            right_buf: vec![vec![0.0; block_size]; nbuf],
+            channel_left_buf: Vec::new(),
+            channel_right_buf: Vec::new(),
+            fx_left_buf: FxBuf::new(block_size),
+            fx_right_buf: FxBuf::new(block_size),
+            dsp_buf: vec![0.0; block_size],
+            block_size,
+            cur: block_size,
+            block_output: BlockOutput::Mixed,
             #[cfg(feature = "i16-out")]
             i16_output: Default::default(),
         }
@@ -57,6 +85,11 @@ impl Core {
             .right_buf
             .iter_mut()
             .for_each(|buff| buff.fill(0.0));
+        self.output
+            .channel_left_buf
+            .iter_mut()
+            .chain(self.output.channel_right_buf.iter_mut())
+            .for_each(|buff| buff.fill(0.0));
 
         self.output.fx_left_buf.reverb.fill(0.0);
         self.output.fx_left_buf.chorus.fill(0.0);
@@ -65,39 +98,141 @@ impl Core {
         self.output.fx_right_buf.chorus.fill(0.0);
     }
 
-    #[allow(clippy::too_many_arguments)]
-    fn write_voices(&mut self) {
+    fn write_voices(&mut self, output: BlockOutput) {
         for voice in self.voices.iter_mut().filter(|v| v.is_playing()) {
-            // The output associated with a MIDI channel is wrapped around
-            // using the number of audio groups as modulo divider.  This is
-            // typically the number of output channels on the 'sound card',
-            // as long as the LADSPA Fx unit is not used. In case of LADSPA
-            // unit, think of it as subgroups on a mixer.
-            //
-            // For example: Assume that the number of groups is set to 2.
-            // Then MIDI channel 1, 3, 5, 7 etc. go to output 1, channels 2,
-            // 4, 6, 8 etc to output 2.  Or assume 3 groups: Then MIDI
-            // channels 1, 4, 7, 10 etc go to output 1; 2, 5, 8, 11 etc to
-            // output 2, 3, 6, 9, 12 etc to output 3.
-            let mut auchan = voice.channel_id();
-            auchan %= self.settings.audio_groups as usize;
+            let channel = &self.channels[voice.channel_id()];
+            let auchan = channel.audio_group(self.settings.audio_groups as usize);
 
-            voice.write(
-                &self.channels[voice.channel_id()],
-                self.settings.min_note_length_ticks,
+            let (left_buf, right_buf) = if output == BlockOutput::Channels {
+                (
+                    &mut self.output.channel_left_buf[voice.channel_id()],
+                    &mut self.output.channel_right_buf[voice.channel_id()],
+                )
+            } else {
                 (
                     &mut self.output.left_buf[auchan],
                     &mut self.output.right_buf[auchan],
+                )
+            };
+
+            voice.write(
+                channel,
+                self.settings.min_note_length_ticks,
+                &mut self.output.dsp_buf,
+                (left_buf, right_buf),
+                (
+                    (self.settings.reverb_active && channel.reverb_send())
+                        .then_some(&mut self.output.fx_left_buf.reverb[..]),
+                    (self.settings.chorus_active && channel.chorus_send())
+                        .then_some(&mut self.output.fx_left_buf.chorus[..]),
                 ),
-                &mut self.output.fx_left_buf,
-                self.settings.reverb_active,
-                self.settings.chorus_active,
             );
         }
     }
 
-    fn one_block(&mut self, do_not_mix_fx_to_out: bool) {
+    /// Frame of the next sample that will be read
+    fn frame(&self) -> usize {
+        self.ticks + self.output.cur - self.output.block_size
+    }
+
+    /// Queue `event`, to take effect `offset` frames after the next sample that will be read
+    pub(crate) fn queue_event(&mut self, offset: usize, event: MidiEvent) {
+        let frame = self.frame() + offset;
+        // After the events of the same frame, to keep their order
+        let id = self.events.partition_point(|(f, _)| *f <= frame);
+        self.events.insert(id, (frame, event));
+    }
+
+    /// Handle the queued events of the next block, the notes start on their exact frame.
+    ///
+    /// The other events take effect at the start of the block, the voices are only updated
+    /// once per block. Events of a frame that was already rendered also take effect at the
+    /// start of the block.
+    fn handle_queued_events(&mut self) {
+        while let Some(&(frame, event)) = self.events.front() {
+            if frame >= self.ticks + self.output.block_size {
+                break;
+            }
+            self.events.pop_front();
+
+            self.voices
+                .set_start_delay(frame.saturating_sub(self.ticks));
+            if let Err(err) = midi::handle_event(self, event) {
+                log::trace!("Failed to handle timed MIDI event: {err}");
+            }
+        }
+        self.voices.set_start_delay(0);
+    }
+
+    /// Make the rest of the current block readable as `output`, when it was rendered for
+    /// another write function.
+    #[cold]
+    fn convert_block(&mut self, output: BlockOutput) {
+        let rest = self.output.cur..self.output.block_size;
+
+        // The voices were written to the channel buffers, back to their audio groups
+        if self.output.block_output == BlockOutput::Channels {
+            let out = &mut self.output;
+            let channel_bufs = out.channel_left_buf.iter().zip(&out.channel_right_buf);
+            for (channel, (channel_left, channel_right)) in self.channels.iter().zip(channel_bufs) {
+                let group = channel.audio_group(self.settings.audio_groups as usize);
+                for i in rest.clone() {
+                    out.left_buf[group][i] += channel_left[i];
+                    out.right_buf[group][i] += channel_right[i];
+                }
+            }
+            out.channel_left_buf.clear();
+            out.channel_right_buf.clear();
+        }
+
+        match (self.output.block_output, output) {
+            // The effects returns are already mixed into the first group,
+            // only their inputs are left in the effects buffers
+            (BlockOutput::Mixed, BlockOutput::Groups) => {
+                let fx = &mut self.output;
+                for buf in [
+                    &mut fx.fx_left_buf.reverb,
+                    &mut fx.fx_left_buf.chorus,
+                    &mut fx.fx_right_buf.reverb,
+                    &mut fx.fx_right_buf.chorus,
+                ] {
+                    buf[rest.clone()].fill(0.0);
+                }
+            }
+            (BlockOutput::Groups | BlockOutput::Channels, BlockOutput::Mixed) => {
+                let out = &mut self.output;
+                let (left, right) = (&mut out.left_buf[0], &mut out.right_buf[0]);
+                for (fx_left, fx_right) in [
+                    (&out.fx_left_buf.reverb, &out.fx_right_buf.reverb),
+                    (&out.fx_left_buf.chorus, &out.fx_right_buf.chorus),
+                ] {
+                    for i in rest.clone() {
+                        left[i] += fx_left[i];
+                        right[i] += fx_right[i];
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        self.output.block_output = output;
+    }
+
+    /// Render the next block if the current one was fully read, otherwise make sure its rest
+    /// can be read as `output`
+    fn next_block(&mut self, output: BlockOutput) {
+        if self.output.cur == self.output.block_size {
+            self.one_block(output);
+            self.output.cur = 0;
+        } else if self.output.block_output != output {
+            self.convert_block(output);
+        }
+    }
+
+    fn one_block(&mut self, output: BlockOutput) {
+        self.handle_queued_events();
         self.clear_buffers();
+        self.output.block_output = output;
 
         // Set up the reverb / chorus buffers only, when the effect is
         // enabled on synth level.  Nonexisting buffers are detected in the
@@ -105,12 +240,12 @@ impl Core {
         // in that case.
 
         // call all playing synthesis processes
-        self.write_voices();
+        self.write_voices(output);
 
         // if multi channel output, don't mix the output of the chorus and
         // reverb in the final output. The effects outputs are send
         // separately.
-        if do_not_mix_fx_to_out {
+        if output != BlockOutput::Mixed {
             // send to reverb
             if self.settings.reverb_active {
                 self.reverb.process_replace(
@@ -146,15 +281,12 @@ impl Core {
             }
         }
 
-        self.ticks += BUFSIZE;
+        self.ticks += self.output.block_size;
     }
 
     #[inline]
     pub fn read_next(&mut self) -> (f32, f32) {
-        if self.output.cur == 64 {
-            self.one_block(false);
-            self.output.cur = 0;
-        }
+        self.next_block(BlockOutput::Mixed);
 
         let out = (
             self.output.left_buf[0][self.output.cur],
@@ -163,4 +295,156 @@ impl Core {
         self.output.cur += 1;
         out
     }
+
+    /// Write every audio group to its own stereo buffer pair, and the effects returns to `fx`
+    /// (reverb, then chorus) instead of mixing them into the first group.
+    pub fn write_multi(
+        &mut self,
+        groups: &mut [(&mut [f32], &mut [f32])],
+        fx: &mut [(&mut [f32], &mut [f32])],
+    ) {
+        let len = groups
+            .iter()
+            .chain(fx.iter())
+            .map(|(left, right)| left.len().min(right.len()))
+            .min()
+            .unwrap_or(0);
+
+        let mut done = 0;
+        while done < len {
+            self.next_block(BlockOutput::Groups);
+
+            let cur = self.output.cur;
+            let count = (self.output.block_size - cur).min(len - done);
+            let src = cur..cur + count;
+            let dst = done..done + count;
+
+            for (id, (left, right)) in groups.iter_mut().enumerate() {
+                match (self.output.left_buf.get(id), self.output.right_buf.get(id)) {
+                    (Some(left_buf), Some(right_buf)) => {
+                        left[dst.clone()].copy_from_slice(&left_buf[src.clone()]);
+                        right[dst.clone()].copy_from_slice(&right_buf[src.clone()]);
+                    }
+                    _ => {
+                        left[dst.clone()].fill(0.0);
+                        right[dst.clone()].fill(0.0);
+                    }
+                }
+            }
+
+            let fx_bufs = [
+                (
+                    &self.output.fx_left_buf.reverb,
+                    &self.output.fx_right_buf.reverb,
+                ),
+                (
+                    &self.output.fx_left_buf.chorus,
+                    &self.output.fx_right_buf.chorus,
+                ),
+            ];
+            for ((left, right), (left_buf, right_buf)) in fx.iter_mut().zip(fx_bufs) {
+                left[dst.clone()].copy_from_slice(&left_buf[src.clone()]);
+                right[dst.clone()].copy_from_slice(&right_buf[src.clone()]);
+            }
+
+            done += count;
+            self.output.cur += count;
+        }
+    }
+
+    /// Render `len` frames of every MIDI channel, and of the effects returns
+    pub fn render_stems(&mut self, len: usize) -> Stems {
+        let block_size = self.output.block_size;
+        self.output
+            .channel_left_buf
+            .resize(self.channels.len(), vec![0.0; block_size]);
+        self.output
+            .channel_right_buf
+            .resize(self.channels.len(), vec![0.0; block_size]);
+
+        let mut stems = Stems {
+            channels: vec![StereoBuffer::new(len); self.channels.len()],
+            dry: StereoBuffer::new(len),
+            reverb: StereoBuffer::new(len),
+            chorus: StereoBuffer::new(len),
+        };
+
+        let mut done = 0;
+        while done < len {
+            if self.output.cur == block_size {
+                self.one_block(BlockOutput::Channels);
+                self.output.cur = 0;
+            }
+
+            let cur = self.output.cur;
+            let count = (block_size - cur).min(len - done);
+            let src = cur..cur + count;
+            let dst = done..done + count;
+
+            // Rest of a block rendered by another write function, it has no channel stems
+            if self.output.block_output != BlockOutput::Channels {
+                self.convert_block(BlockOutput::Groups);
+
+                let group_bufs = self
+                    .output
+                    .left_buf
+                    .iter()
+                    .zip(self.output.right_buf.iter());
+                for (left_buf, right_buf) in group_bufs {
+                    for (out, s) in stems.dry.left[dst.clone()]
+                        .iter_mut()
+                        .zip(&left_buf[src.clone()])
+                    {
+                        *out += s;
+                    }
+                    for (out, s) in stems.dry.right[dst.clone()]
+                        .iter_mut()
+                        .zip(&right_buf[src.clone()])
+                    {
+                        *out += s;
+                    }
+                }
+            }
+
+            let channel_bufs = self
+                .output
+                .channel_left_buf
+                .iter()
+                .zip(self.output.channel_right_buf.iter());
+            let fx_bufs = [
+                (
+                    &self.output.fx_left_buf.reverb,
+                    &self.output.fx_right_buf.reverb,
+                ),
+                (
+                    &self.output.fx_left_buf.chorus,
+                    &self.output.fx_right_buf.chorus,
+                ),
+            ];
+            let stem_bufs = stems
+                .channels
+                .iter_mut()
+                .chain([&mut stems.reverb, &mut stems.chorus]);
+
+            for (stem, (left_buf, right_buf)) in stem_bufs.zip(channel_bufs.chain(fx_bufs)) {
+                stem.left[dst.clone()].copy_from_slice(&left_buf[src.clone()]);
+                stem.right[dst.clone()].copy_from_slice(&right_buf[src.clone()]);
+            }
+
+            done += count;
+            self.output.cur += count;
+        }
+
+        for channel in stems.channels.iter() {
+            stems.dry.mix(channel);
+        }
+
+        // Back to the audio groups for the next block, once the current one is fully read
+        if self.output.cur == block_size {
+            self.output.channel_left_buf.clear();
+            self.output.channel_right_buf.clear();
+        }
+
+        stems
+    }
 }
@@ -23,6 +23,15 @@ pub enum LoadError {
     SampleNotFound {
         name: String,
     },
+    /// A compressed sample could not be decoded, see [`crate::LoadOptions::broken_samples`]
+    SampleDecode {
+        name: String,
+        reason: String,
+    },
+    /// A font created with [`crate::SoundFontBuilder`] is not valid
+    InvalidFont {
+        reason: String,
+    },
 }
 
 impl From<soundfont::Error> for LoadError {
@@ -53,6 +62,101 @@ impl std::fmt::Display for LoadError {
             Self::SampleNotFound { name } => {
                 write!(f, "Sample {name:?} not found")?;
             }
+            Self::SampleDecode { name, reason } => {
+                write!(f, "Failed to decode sample {name:?}: {reason}")?;
+            }
+            Self::InvalidFont { reason } => {
+                write!(f, "Invalid SoundFont: {reason}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum SmfError {
+    Io(std::io::Error),
+    InvalidHeader,
+    UnsupportedFormat(u16),
+    UnexpectedEnd { track: usize },
+    MissingRunningStatus { track: usize },
+    InvalidStatus { track: usize, status: u8 },
+}
+
+impl From<std::io::Error> for SmfError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl std::error::Error for SmfError {}
+impl std::fmt::Display for SmfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(error) => {
+                write!(f, "IO error while reading MIDI file: {error}")?;
+            }
+            Self::InvalidHeader => {
+                write!(f, "Invalid MIDI file header")?;
+            }
+            Self::UnsupportedFormat(format) => {
+                write!(f, "Unsupported MIDI file format: {format}")?;
+            }
+            Self::UnexpectedEnd { track } => {
+                write!(f, "Unexpected end of track {track}")?;
+            }
+            Self::MissingRunningStatus { track } => {
+                write!(f, "Data byte without running status in track {track}")?;
+            }
+            Self::InvalidStatus { track, status } => {
+                write!(f, "Invalid status byte {status:#04x} in track {track}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Error while parsing a Scala scale (`.scl`) or keyboard mapping (`.kbm`) file
+///
+/// Line numbers start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScalaError {
+    UnexpectedEnd { line: usize },
+    InvalidNumber { line: usize, value: String },
+    InvalidPitch { line: usize, value: String },
+    UnmappedReferenceKey { line: usize },
+}
+
+impl ScalaError {
+    /// Line of the file at which the error occurred
+    pub fn line(&self) -> usize {
+        match self {
+            Self::UnexpectedEnd { line }
+            | Self::InvalidNumber { line, .. }
+            | Self::InvalidPitch { line, .. }
+            | Self::UnmappedReferenceKey { line } => *line,
+        }
+    }
+}
+
+impl std::error::Error for ScalaError {}
+impl std::fmt::Display for ScalaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedEnd { line } => {
+                write!(f, "Unexpected end of file at line {line}")?;
+            }
+            Self::InvalidNumber { line, value } => {
+                write!(f, "Invalid number {value:?} at line {line}")?;
+            }
+            Self::InvalidPitch { line, value } => {
+                write!(f, "Invalid pitch {value:?} at line {line}")?;
+            }
+            Self::UnmappedReferenceKey { line } => {
+                write!(f, "Reference key at line {line} is not mapped")?;
+            }
         }
 
         Ok(())
@@ -88,6 +192,11 @@ pub enum SettingsError {
     AudioChannelRange(RangeError<u8>),
     AudioGroupsRange(RangeError<u8>),
     SammpleRateRange(RangeError<f32>),
+    BlockSizeRange(RangeError<u16>),
+    DeviceIdRange(RangeError<u8>),
+    SoftPedalAttenuationRange(RangeError<f32>),
+    SoftPedalCutoffRange(RangeError<f32>),
+    Hold2ReleaseRange(RangeError<f32>),
 
     /// Requested number of MIDI channels is not a multiple of 16. Increase the number of channels to the next multiple.
     MidiChannelsIsNotMultipleOf16,
@@ -112,6 +221,21 @@ impl std::fmt::Display for SettingsError {
             SettingsError::SammpleRateRange(range_error) => {
                 write!(f, "SammpleRate {range_error}")?;
             }
+            SettingsError::BlockSizeRange(range_error) => {
+                write!(f, "BlockSize {range_error}")?;
+            }
+            SettingsError::DeviceIdRange(range_error) => {
+                write!(f, "DeviceId {range_error}")?;
+            }
+            SettingsError::SoftPedalAttenuationRange(range_error) => {
+                write!(f, "SoftPedalAttenuation {range_error}")?;
+            }
+            SettingsError::SoftPedalCutoffRange(range_error) => {
+                write!(f, "SoftPedalCutoff {range_error}")?;
+            }
+            SettingsError::Hold2ReleaseRange(range_error) => {
+                write!(f, "Hold2Release {range_error}")?;
+            }
             SettingsError::MidiChannelsIsNotMultipleOf16 => {
                 write!(f, "MidiChannels is not a multiple of 16")?;
             }
@@ -126,6 +250,7 @@ pub enum OxiError {
     KeyOutOfRange,
     VelocityOutOfRange,
     ChannelOutOfRange,
+    AudioGroupOutOfRange,
     CtrlOutOfRange,
     CCValueOutOfRange,
     ProgramOutOfRange,
@@ -139,6 +264,10 @@ pub enum OxiError {
         sfont_id: SoundFontId,
     },
     InvalidPolyphony,
+    TuningNotFound {
+        bank: u8,
+        program: u8,
+    },
 }
 
 impl std::error::Error for OxiError {}
@@ -155,6 +284,9 @@ impl std::fmt::Display for OxiError {
             OxiError::ChannelOutOfRange => {
                 write!(f, "Channel out of range")?;
             }
+            OxiError::AudioGroupOutOfRange => {
+                write!(f, "Audio group out of range")?;
+            }
             OxiError::CtrlOutOfRange => {
                 write!(f, "Ctrl out of range (0-127)")?;
             }
@@ -186,6 +318,12 @@ impl std::fmt::Display for OxiError {
             OxiError::InvalidPolyphony => {
                 write!(f, "Only polyphony >= 1 is allowed")?;
             }
+            OxiError::TuningNotFound { bank, program } => {
+                write!(
+                    f,
+                    "There is no tuning with bank number {bank} and program number {program}"
+                )?;
+            }
         };
 
         Ok(())
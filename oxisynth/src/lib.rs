@@ -7,25 +7,46 @@
 //! To send events use: [`Synth::send_event()`] function.
 //!
 //! To write the results to a buffer use [`Synth::write()`] function.
+//! With the `wav` feature, the `wav` module writes them to WAV or raw PCM files.
 
 mod api;
 mod arena;
 mod core;
 mod error;
 mod midi_event;
+mod midi_parser;
+pub mod smf;
+#[cfg(feature = "wav")]
+pub mod wav;
 
 mod unsafe_stuff;
 
-pub use api::Tuning;
-pub use core::{GeneratorType, InterpolationMethod, Preset, SoundFont};
-pub use error::{OxiError, RangeError, SettingsError};
-pub use midi_event::MidiEvent;
+#[cfg(test)]
+mod test_util;
+
+pub use api::{KeyboardMapping, Scale, Stems, StereoBuffer, Tuning};
+pub use core::{
+    BrokenSamples, GeneratorType, Instrument, InstrumentDescriptor, InstrumentId, InstrumentZone,
+    InterpolationMethod, LoadOptions, Mod, NotePriority, Preset, PresetDescriptor, PresetZone,
+    Sample, SampleDescriptor, SampleId, SoundFont, SoundFontBuilder, ZoneDescriptor,
+};
+pub use error::{LoadError, OxiError, RangeError, ScalaError, SettingsError, SmfError};
+pub use midi_event::{MidiEvent, TimedEvent};
+pub use midi_parser::{MidiMessage, MidiParser, SystemMessage};
 
 #[doc(inline)]
 pub use oxisynth_chorus::ChorusParams;
 #[doc(inline)]
 pub use oxisynth_reverb::ReverbParams;
 
+/// Modulator types of the SoundFont parser, used by [`Mod`] and [`ZoneDescriptor::modulators`]
+pub mod modulator {
+    pub use soundfont::raw::{
+        ControllerPalette, GeneralPalette, GeneratorType, Modulator, ModulatorSource,
+        ModulatorTransform, SourceDirection, SourcePolarity, SourceType,
+    };
+}
+
 #[doc(hidden)]
 pub use arena::Index;
 pub type SoundFontId = Index<SoundFont>;
@@ -53,6 +74,9 @@ pub struct SynthDescriptor {
     /// Min: 1
     /// Max: 128
     pub audio_channels: u8,
+    /// Number of stereo outputs of [`Synth::write_multi()`], the MIDI channels are wrapped
+    /// around them
+    ///
     /// Def: 1
     /// Min: 1
     /// Max: 128
@@ -65,6 +89,39 @@ pub struct SynthDescriptor {
     /// Min: 0
     /// Max: 65535
     pub min_note_length: u16,
+    /// Number of frames rendered at once.
+    ///
+    /// Small blocks lower the latency of the events, large blocks lower the per-block overhead.
+    /// The envelopes, LFOs and controller changes are updated once per block.
+    ///
+    /// Def: 64
+    /// Min: 16
+    /// Max: 1024
+    pub block_size: u16,
+    /// SysEx device ID of the synth, messages sent to `0x7F` (all call) are always handled
+    ///
+    /// Def: 16
+    /// Min: 0
+    /// Max: 126
+    pub device_id: u8,
+    /// Attenuation of the notes started while the soft pedal (CC67) is down, in centibels
+    ///
+    /// Def: 60.0
+    /// Min: 0.0
+    /// Max: 1440.0
+    pub soft_pedal_attenuation: f32,
+    /// Filter cutoff reduction of the notes started while the soft pedal (CC67) is down, in cents
+    ///
+    /// Def: 1200.0
+    /// Min: 0.0
+    /// Max: 12000.0
+    pub soft_pedal_cutoff: f32,
+    /// Release time increase of the notes released while Hold2 (CC69) is down, in timecents
+    ///
+    /// Def: 2400.0
+    /// Min: 0.0
+    /// Max: 12000.0
+    pub hold2_release: f32,
 }
 
 impl Default for SynthDescriptor {
@@ -82,6 +139,11 @@ impl Default for SynthDescriptor {
             audio_groups: 1,
             sample_rate: 44100.0,
             min_note_length: 10,
+            block_size: 64,
+            device_id: 16,
+            soft_pedal_attenuation: 60.0,
+            soft_pedal_cutoff: 1200.0,
+            hold2_release: 2400.0,
         }
     }
 }
@@ -116,12 +178,9 @@ impl Synth {
 #[cfg(test)]
 mod test {
     use crate::{MidiEvent, SoundFont, Synth, SynthDescriptor};
-    use std::{fs::File, io::Write};
 
     #[test]
     fn synth_sf2() {
-        let mut pcm = File::create("Boomwhacker.sf2.pcm").unwrap();
-
         let mut synth = Synth::new(SynthDescriptor::default()).unwrap();
 
         let mut file = std::fs::File::open("../testdata/Boomwhacker.sf2").unwrap();
@@ -129,7 +188,8 @@ mod test {
 
         synth.add_font(font, true);
 
-        let mut samples = [0f32; 44100 * 2];
+        let mut samples = vec![0f32; 44100 * 2];
+        let peak = |samples: &[f32]| samples.iter().fold(0f32, |max, s| max.max(s.abs()));
 
         synth
             .send_event(MidiEvent::NoteOn {
@@ -139,9 +199,8 @@ mod test {
             })
             .unwrap();
 
-        synth.write(samples.as_mut());
-        pcm.write_all(crate::unsafe_stuff::slice_f32_to_u8(&samples))
-            .unwrap();
+        synth.write(samples.as_mut_slice());
+        assert!(peak(&samples) > 0.01);
 
         synth
             .send_event(MidiEvent::NoteOff {
@@ -150,10 +209,7 @@ mod test {
             })
             .unwrap();
 
-        synth.write(samples.as_mut());
-        pcm.write_all(crate::unsafe_stuff::slice_f32_to_u8(&samples))
-            .unwrap();
-
-        drop(synth);
+        synth.write(samples.as_mut_slice());
+        assert!(peak(&samples[samples.len() - 2048..]) < 0.001);
     }
 }
@@ -3,7 +3,7 @@ use crate::error::{range_check, OxiError};
 pub type U7 = u8;
 pub type U14 = u16;
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum MidiEvent {
     /// Send a noteon message.
     NoteOn {
@@ -58,6 +58,19 @@ pub enum MidiEvent {
     SystemReset,
 }
 
+/// A MIDI event with the frame at which it takes effect, see [`Synth::render()`](crate::Synth::render)
+///
+/// Only note-ons are sample-accurate. Note-offs, controller changes, pitch bends and the other
+/// events take effect at the start of the block that contains their frame, as the envelopes
+/// and controllers are updated once per block, see
+/// [`SynthDescriptor::block_size`](crate::SynthDescriptor::block_size).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TimedEvent {
+    /// Offset from the first frame of the rendered buffer
+    pub frame: usize,
+    pub event: MidiEvent,
+}
+
 impl MidiEvent {
     pub fn check(self) -> Result<Self, OxiError> {
         match &self {
@@ -0,0 +1,450 @@
+use crate::midi_event::MidiEvent;
+
+/// Longest SysEx message kept by [`MidiParser`], longer ones are dropped
+const MAX_SYSEX_LEN: usize = 4096;
+
+/// System messages that have no [`MidiEvent`] equivalent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemMessage {
+    MtcQuarterFrame(u8),
+    SongPosition(u16),
+    SongSelect(u8),
+    TuneRequest,
+    TimingClock,
+    Start,
+    Continue,
+    Stop,
+    ActiveSensing,
+}
+
+/// Message decoded by [`MidiParser`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MidiMessage<'a> {
+    Event(MidiEvent),
+    /// Complete SysEx message, without the `F0`/`F7` framing
+    SysEx(&'a [u8]),
+    System(SystemMessage),
+}
+
+/// Stateful MIDI 1.0 byte-stream decoder
+///
+/// Handles running status, system real-time bytes interleaved with other messages
+/// and SysEx framing. SysEx messages longer than 4096 bytes are dropped.
+///
+/// ```
+/// use oxisynth::{MidiEvent, MidiMessage, MidiParser};
+///
+/// let mut parser = MidiParser::new();
+/// let mut events = Vec::new();
+///
+/// // NoteOn followed by a running status NoteOn, with a timing clock in between
+/// parser.feed(&[0x90, 60, 0xF8, 100, 64, 90], |msg| {
+///     if let MidiMessage::Event(e) = msg {
+///         events.push(e);
+///     }
+/// });
+///
+/// assert_eq!(events[1], MidiEvent::NoteOn { channel: 0, key: 64, vel: 90 });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MidiParser {
+    running_status: Option<u8>,
+    data: [u8; 2],
+    data_len: usize,
+
+    sysex: Vec<u8>,
+    in_sysex: bool,
+}
+
+impl MidiParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop any partially received message and the running status.
+    pub fn reset(&mut self) {
+        self.running_status = None;
+        self.data_len = 0;
+        self.in_sysex = false;
+        self.sysex.clear();
+    }
+
+    /// Decode every byte of `bytes`, calling `f` for each complete message.
+    pub fn feed(&mut self, bytes: &[u8], mut f: impl FnMut(MidiMessage)) {
+        for byte in bytes {
+            if let Some(msg) = self.push(*byte) {
+                f(msg);
+            }
+        }
+    }
+
+    /// Decode one byte, returns a message once it is complete.
+    pub fn push(&mut self, byte: u8) -> Option<MidiMessage<'_>> {
+        match byte {
+            // System real-time, may appear anywhere, even inside of other messages
+            0xF8..=0xFF => {
+                return match byte {
+                    0xF8 => Some(MidiMessage::System(SystemMessage::TimingClock)),
+                    0xFA => Some(MidiMessage::System(SystemMessage::Start)),
+                    0xFB => Some(MidiMessage::System(SystemMessage::Continue)),
+                    0xFC => Some(MidiMessage::System(SystemMessage::Stop)),
+                    0xFE => Some(MidiMessage::System(SystemMessage::ActiveSensing)),
+                    0xFF => Some(MidiMessage::Event(MidiEvent::SystemReset)),
+                    // Undefined
+                    _ => None,
+                };
+            }
+            0xF7 => {
+                self.running_status = None;
+                if self.in_sysex {
+                    self.in_sysex = false;
+                    return Some(MidiMessage::SysEx(&self.sysex));
+                }
+                return None;
+            }
+            0xF0 => {
+                self.running_status = None;
+                self.in_sysex = true;
+                self.sysex.clear();
+                return None;
+            }
+            0x80..=0xF6 => {
+                if self.in_sysex {
+                    log::warn!("SysEx message interrupted by status byte {byte:#04x}");
+                    self.in_sysex = false;
+                }
+
+                self.data_len = 0;
+                self.running_status = Some(byte);
+
+                // System common messages cancel running status
+                match byte {
+                    0xF6 => {
+                        self.running_status = None;
+                        return Some(MidiMessage::System(SystemMessage::TuneRequest));
+                    }
+                    0xF4 | 0xF5 => {
+                        // Undefined
+                        self.running_status = None;
+                        return None;
+                    }
+                    _ => return None,
+                }
+            }
+            _ => {}
+        }
+
+        // Data byte
+        if self.in_sysex {
+            if self.sysex.len() < MAX_SYSEX_LEN {
+                self.sysex.push(byte);
+            } else {
+                // The rest of the message is dropped along with the data bytes,
+                // as there is no running status
+                log::warn!("SysEx message longer than {MAX_SYSEX_LEN} bytes dropped");
+                self.in_sysex = false;
+                self.sysex.clear();
+            }
+            return None;
+        }
+
+        let status = self.running_status?;
+
+        self.data[self.data_len] = byte;
+        self.data_len += 1;
+
+        if self.data_len < data_len(status) {
+            return None;
+        }
+        self.data_len = 0;
+
+        let [data1, data2] = self.data;
+
+        let msg = match status {
+            0xF1 => MidiMessage::System(SystemMessage::MtcQuarterFrame(data1)),
+            0xF2 => MidiMessage::System(SystemMessage::SongPosition(
+                ((data2 as u16) << 7) | data1 as u16,
+            )),
+            0xF3 => MidiMessage::System(SystemMessage::SongSelect(data1)),
+            _ => MidiMessage::Event(channel_message(status, data1, data2)),
+        };
+
+        // System common messages don't have running status
+        if status >= 0xF0 {
+            self.running_status = None;
+        }
+
+        Some(msg)
+    }
+}
+
+/// Number of data bytes following the status byte
+fn data_len(status: u8) -> usize {
+    match status & 0xF0 {
+        0xC0 | 0xD0 => 1,
+        0xF0 => match status {
+            0xF1 | 0xF3 => 1,
+            0xF2 => 2,
+            _ => 0,
+        },
+        _ => 2,
+    }
+}
+
+/// Map a MIDI 1.0 channel voice or mode message onto [`MidiEvent`]
+pub(crate) fn channel_message(status: u8, data1: u8, data2: u8) -> MidiEvent {
+    let channel = status & 0x0F;
+
+    match status & 0xF0 {
+        0x80 => MidiEvent::NoteOff {
+            channel,
+            key: data1,
+        },
+        0x90 => MidiEvent::NoteOn {
+            channel,
+            key: data1,
+            vel: data2,
+        },
+        0xA0 => MidiEvent::PolyphonicKeyPressure {
+            channel,
+            key: data1,
+            value: data2,
+        },
+        0xB0 => match data1 {
+            120 => MidiEvent::AllSoundOff { channel },
+            123 => MidiEvent::AllNotesOff { channel },
+            _ => MidiEvent::ControlChange {
+                channel,
+                ctrl: data1,
+                value: data2,
+            },
+        },
+        0xC0 => MidiEvent::ProgramChange {
+            channel,
+            program_id: data1,
+        },
+        0xD0 => MidiEvent::ChannelPressure {
+            channel,
+            value: data1,
+        },
+        0xE0 => MidiEvent::PitchBend {
+            channel,
+            value: ((data2 as u16) << 7) | data1 as u16,
+        },
+        _ => unreachable!("Not a channel message"),
+    }
+}
+
+impl MidiEvent {
+    /// Decode a single complete MIDI message, as delivered by most MIDI APIs.
+    ///
+    /// Running status and SysEx are not supported here, use [`MidiParser`] for byte streams.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        match *bytes {
+            [0xFF] => Some(Self::SystemReset),
+            [status @ (0xC0..=0xDF), data1] if data1 < 0x80 => {
+                Some(channel_message(status, data1, 0))
+            }
+            [status @ (0x80..=0xBF | 0xE0..=0xEF), data1, data2]
+                if data1 < 0x80 && data2 < 0x80 =>
+            {
+                Some(channel_message(status, data1, data2))
+            }
+            _ => None,
+        }
+    }
+
+    /// Append the MIDI 1.0 encoding of the event to `out`.
+    ///
+    /// Channels above 15 are wrapped, as they can't be addressed on a single MIDI port.
+    /// Data values above 127 are masked to 7 bits.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        let ch = |channel: u8| channel & 0x0F;
+        let d = |data: u8| data & 0x7F;
+
+        match *self {
+            Self::NoteOn { channel, key, vel } => {
+                out.extend_from_slice(&[0x90 | ch(channel), d(key), d(vel)]);
+            }
+            Self::NoteOff { channel, key } => {
+                out.extend_from_slice(&[0x80 | ch(channel), d(key), 0]);
+            }
+            Self::ControlChange {
+                channel,
+                ctrl,
+                value,
+            } => {
+                out.extend_from_slice(&[0xB0 | ch(channel), d(ctrl), d(value)]);
+            }
+            Self::AllNotesOff { channel } => {
+                out.extend_from_slice(&[0xB0 | ch(channel), 123, 0]);
+            }
+            Self::AllSoundOff { channel } => {
+                out.extend_from_slice(&[0xB0 | ch(channel), 120, 0]);
+            }
+            Self::PitchBend { channel, value } => {
+                out.extend_from_slice(&[
+                    0xE0 | ch(channel),
+                    (value & 0x7F) as u8,
+                    ((value >> 7) & 0x7F) as u8,
+                ]);
+            }
+            Self::ProgramChange {
+                channel,
+                program_id,
+            } => {
+                out.extend_from_slice(&[0xC0 | ch(channel), d(program_id)]);
+            }
+            Self::ChannelPressure { channel, value } => {
+                out.extend_from_slice(&[0xD0 | ch(channel), d(value)]);
+            }
+            Self::PolyphonicKeyPressure {
+                channel,
+                key,
+                value,
+            } => {
+                out.extend_from_slice(&[0xA0 | ch(channel), d(key), d(value)]);
+            }
+            Self::SystemReset => {
+                out.push(0xFF);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MidiMessage, MidiParser, SystemMessage};
+    use crate::MidiEvent;
+
+    fn collect(parser: &mut MidiParser, bytes: &[u8]) -> Vec<String> {
+        let mut out = Vec::new();
+        parser.feed(bytes, |msg| out.push(format!("{msg:?}")));
+        out
+    }
+
+    #[test]
+    fn running_status_and_realtime() {
+        let mut parser = MidiParser::new();
+        let mut events = Vec::new();
+
+        parser.feed(
+            &[0x91, 60, 0xF8, 100, 62, 0xFE, 0, 0xC2, 5, 7],
+            |msg| match msg {
+                MidiMessage::Event(e) => events.push(e),
+                MidiMessage::System(s) => {
+                    assert!(matches!(
+                        s,
+                        SystemMessage::TimingClock | SystemMessage::ActiveSensing
+                    ))
+                }
+                MidiMessage::SysEx(_) => unreachable!(),
+            },
+        );
+
+        assert_eq!(
+            events,
+            [
+                MidiEvent::NoteOn {
+                    channel: 1,
+                    key: 60,
+                    vel: 100
+                },
+                MidiEvent::NoteOn {
+                    channel: 1,
+                    key: 62,
+                    vel: 0
+                },
+                MidiEvent::ProgramChange {
+                    channel: 2,
+                    program_id: 5
+                },
+                MidiEvent::ProgramChange {
+                    channel: 2,
+                    program_id: 7
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn sysex() {
+        let mut parser = MidiParser::new();
+
+        // GM System On, with a clock byte in the middle
+        let out = collect(&mut parser, &[0xF0, 0x7E, 0x7F, 0xF8, 0x09, 0x01, 0xF7]);
+        assert_eq!(out, ["System(TimingClock)", "SysEx([126, 127, 9, 1])"]);
+
+        // SysEx cancels running status
+        let out = collect(&mut parser, &[0x90, 60, 100, 0xF0, 1, 0xF7, 61, 100]);
+        assert_eq!(
+            out,
+            [
+                "Event(NoteOn { channel: 0, key: 60, vel: 100 })",
+                "SysEx([1])"
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_roundtrip() {
+        let events = [
+            MidiEvent::NoteOn {
+                channel: 3,
+                key: 60,
+                vel: 100,
+            },
+            MidiEvent::NoteOff {
+                channel: 3,
+                key: 60,
+            },
+            MidiEvent::PitchBend {
+                channel: 15,
+                value: 12345,
+            },
+            MidiEvent::AllSoundOff { channel: 1 },
+            MidiEvent::ChannelPressure {
+                channel: 0,
+                value: 12,
+            },
+            MidiEvent::SystemReset,
+        ];
+
+        let mut bytes = Vec::new();
+        for e in events.iter() {
+            e.encode(&mut bytes);
+        }
+
+        let mut parser = MidiParser::new();
+        let mut decoded = Vec::new();
+        parser.feed(&bytes, |msg| {
+            if let MidiMessage::Event(e) = msg {
+                decoded.push(e);
+            }
+        });
+
+        assert_eq!(decoded, events);
+
+        // Out of range data doesn't produce status bytes
+        let mut bytes = Vec::new();
+        MidiEvent::NoteOn {
+            channel: 0,
+            key: 200,
+            vel: 255,
+        }
+        .encode(&mut bytes);
+        assert_eq!(bytes, [0x90, 200 & 0x7F, 0x7F]);
+    }
+
+    #[test]
+    fn sysex_too_long() {
+        let mut parser = MidiParser::new();
+
+        let mut bytes = vec![0xF0];
+        bytes.resize(super::MAX_SYSEX_LEN + 10, 0x01);
+        bytes.extend_from_slice(&[0xF7, 0x90, 60, 100]);
+
+        let out = collect(&mut parser, &bytes);
+        assert_eq!(out, ["Event(NoteOn { channel: 0, key: 60, vel: 100 })"]);
+    }
+}
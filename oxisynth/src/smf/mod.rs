@@ -0,0 +1,432 @@
+//! Standard MIDI File (SMF) support
+//!
+//! [`MidiFile`] parses format 0, 1 and 2 files, and [`MidiPlayer`] renders
+//! them into a [`crate::Synth`].
+
+mod player;
+
+pub use player::MidiPlayer;
+
+use std::io::Read;
+
+use crate::{error::SmfError, midi_parser::channel_message, MidiEvent};
+
+/// Layout of the tracks in a [`MidiFile`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Format 0: a single multi-channel track
+    SingleTrack,
+    /// Format 1: simultaneous tracks sharing one tempo map
+    Parallel,
+    /// Format 2: independent single-track patterns, played one after another
+    Sequential,
+}
+
+/// Meaning of the delta-times in a [`MidiFile`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Division {
+    /// Metrical time, delta-times are in ticks per quarter note
+    TicksPerQuarter(u16),
+    /// Time-code based time, delta-times are subdivisions of a SMPTE frame
+    Smpte {
+        /// Frames per second (24, 25, 29.97 or 30)
+        fps: f32,
+        ticks_per_frame: u8,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetaEvent {
+    SequenceNumber(Option<u16>),
+    /// Text-like events (0x01..=0x0F), `kind` is the meta type byte
+    Text {
+        kind: u8,
+        text: Vec<u8>,
+    },
+    ChannelPrefix(u8),
+    Port(u8),
+    EndOfTrack,
+    /// Microseconds per quarter note
+    Tempo(u32),
+    SmpteOffset {
+        hours: u8,
+        minutes: u8,
+        seconds: u8,
+        frames: u8,
+        subframes: u8,
+    },
+    TimeSignature {
+        numerator: u8,
+        /// Denominator as a negative power of two (2 = quarter note)
+        denominator: u8,
+        clocks_per_click: u8,
+        notated_32nds_per_quarter: u8,
+    },
+    KeySignature {
+        /// Negative for flats, positive for sharps
+        sharps: i8,
+        minor: bool,
+    },
+    SequencerSpecific(Vec<u8>),
+    Unknown {
+        ty: u8,
+        data: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackEventKind {
+    Midi(MidiEvent),
+    /// SysEx message (`F0` event), without the leading `F0`
+    SysEx(Vec<u8>),
+    /// Escaped data (`F7` event), either a SysEx continuation or raw bytes
+    Escape(Vec<u8>),
+    Meta(MetaEvent),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackEvent {
+    /// Ticks since the previous event in the track
+    pub delta: u32,
+    pub kind: TrackEventKind,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Track {
+    pub events: Vec<TrackEvent>,
+}
+
+/// Parsed Standard MIDI File
+#[derive(Debug, Clone, PartialEq)]
+pub struct MidiFile {
+    pub format: Format,
+    pub division: Division,
+    pub tracks: Vec<Track>,
+}
+
+impl MidiFile {
+    /// Read and parse a Standard MIDI File.
+    pub fn load<F: Read>(file: &mut F) -> Result<Self, SmfError> {
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        Self::parse(&data)
+    }
+
+    /// Parse a Standard MIDI File from memory.
+    pub fn parse(data: &[u8]) -> Result<Self, SmfError> {
+        let mut reader = ByteReader::new(data);
+
+        // Some files are wrapped in a RIFF RMID container, skip to the header
+        let Some(start) = data.windows(4).position(|w| w == b"MThd") else {
+            return Err(SmfError::InvalidHeader);
+        };
+        reader.pos = start + 4;
+
+        let header_len = reader.u32().ok_or(SmfError::InvalidHeader)? as usize;
+        if header_len < 6 {
+            return Err(SmfError::InvalidHeader);
+        }
+        let header = reader.bytes(header_len).ok_or(SmfError::InvalidHeader)?;
+
+        let format = match u16::from_be_bytes([header[0], header[1]]) {
+            0 => Format::SingleTrack,
+            1 => Format::Parallel,
+            2 => Format::Sequential,
+            v => return Err(SmfError::UnsupportedFormat(v)),
+        };
+        let ntracks = u16::from_be_bytes([header[2], header[3]]) as usize;
+
+        let division = if header[4] & 0x80 != 0 {
+            let fps = match header[4] as i8 {
+                -24 => 24.0,
+                -25 => 25.0,
+                -29 => 29.97,
+                -30 => 30.0,
+                _ => return Err(SmfError::InvalidHeader),
+            };
+            Division::Smpte {
+                fps,
+                ticks_per_frame: header[5],
+            }
+        } else {
+            match u16::from_be_bytes([header[4], header[5]]) {
+                0 => return Err(SmfError::InvalidHeader),
+                tpq => Division::TicksPerQuarter(tpq),
+            }
+        };
+
+        let mut tracks = Vec::with_capacity(ntracks);
+        while tracks.len() < ntracks {
+            let Some(id) = reader.bytes(4) else {
+                log::warn!(
+                    "SMF header declares {} tracks, but only {} were found",
+                    ntracks,
+                    tracks.len()
+                );
+                break;
+            };
+            let id: [u8; 4] = id.try_into().unwrap();
+            let len = reader.u32().ok_or(SmfError::UnexpectedEnd {
+                track: tracks.len(),
+            })? as usize;
+
+            // Some writers get the length wrong, so be lenient about the last track
+            let len = if len > reader.remaining() {
+                log::warn!("SMF chunk length exceeds the file size, truncating");
+                reader.remaining()
+            } else {
+                len
+            };
+            let chunk = reader.bytes(len).unwrap();
+
+            // Unknown chunks are to be ignored (SMF 1.0, chapter 2)
+            if &id == b"MTrk" {
+                tracks.push(Track::parse(chunk, tracks.len())?);
+            }
+        }
+
+        Ok(Self {
+            format,
+            division,
+            tracks,
+        })
+    }
+}
+
+impl Track {
+    fn parse(data: &[u8], track: usize) -> Result<Self, SmfError> {
+        let mut reader = ByteReader::new(data);
+        let mut events = Vec::new();
+        let mut running_status: Option<u8> = None;
+
+        let eof = || SmfError::UnexpectedEnd { track };
+
+        while reader.remaining() > 0 {
+            let delta = reader.vlq().ok_or_else(eof)?;
+
+            let mut status = reader.u8().ok_or_else(eof)?;
+
+            let kind = match status {
+                0xFF => {
+                    let ty = reader.u8().ok_or_else(eof)?;
+                    let len = reader.vlq().ok_or_else(eof)? as usize;
+                    let data = reader.bytes(len).ok_or_else(eof)?;
+                    TrackEventKind::Meta(MetaEvent::parse(ty, data))
+                }
+                0xF0 => {
+                    let len = reader.vlq().ok_or_else(eof)? as usize;
+                    let mut data = reader.bytes(len).ok_or_else(eof)?;
+                    if let Some((&0xF7, rest)) = data.split_last() {
+                        data = rest;
+                    }
+                    TrackEventKind::SysEx(data.to_vec())
+                }
+                0xF7 => {
+                    let len = reader.vlq().ok_or_else(eof)? as usize;
+                    let data = reader.bytes(len).ok_or_else(eof)?;
+                    TrackEventKind::Escape(data.to_vec())
+                }
+                0xF1..=0xFE => {
+                    return Err(SmfError::InvalidStatus { track, status });
+                }
+                _ => {
+                    let first = if status < 0x80 {
+                        // Running status, the byte we just read is the first data byte
+                        let data = status;
+                        status = running_status.ok_or(SmfError::MissingRunningStatus { track })?;
+                        data
+                    } else {
+                        running_status = Some(status);
+                        reader.u8().ok_or_else(eof)?
+                    };
+
+                    let second = match status & 0xF0 {
+                        0xC0 | 0xD0 => 0,
+                        _ => reader.u8().ok_or_else(eof)?,
+                    };
+
+                    TrackEventKind::Midi(channel_message(status, first & 0x7F, second & 0x7F))
+                }
+            };
+
+            let end_of_track = kind == TrackEventKind::Meta(MetaEvent::EndOfTrack);
+            events.push(TrackEvent { delta, kind });
+
+            if end_of_track {
+                break;
+            }
+        }
+
+        Ok(Self { events })
+    }
+}
+
+impl MetaEvent {
+    fn parse(ty: u8, data: &[u8]) -> Self {
+        match (ty, data) {
+            (0x00, [msb, lsb]) => Self::SequenceNumber(Some(u16::from_be_bytes([*msb, *lsb]))),
+            (0x00, []) => Self::SequenceNumber(None),
+            (0x01..=0x0F, text) => Self::Text {
+                kind: ty,
+                text: text.to_vec(),
+            },
+            (0x20, [channel]) => Self::ChannelPrefix(*channel),
+            (0x21, [port]) => Self::Port(*port),
+            (0x2F, _) => Self::EndOfTrack,
+            (0x51, [a, b, c]) => Self::Tempo(u32::from_be_bytes([0, *a, *b, *c])),
+            (0x54, [hours, minutes, seconds, frames, subframes]) => Self::SmpteOffset {
+                hours: *hours,
+                minutes: *minutes,
+                seconds: *seconds,
+                frames: *frames,
+                subframes: *subframes,
+            },
+            (0x58, [numerator, denominator, clocks_per_click, notated_32nds_per_quarter]) => {
+                Self::TimeSignature {
+                    numerator: *numerator,
+                    denominator: *denominator,
+                    clocks_per_click: *clocks_per_click,
+                    notated_32nds_per_quarter: *notated_32nds_per_quarter,
+                }
+            }
+            (0x59, [sharps, minor]) => Self::KeySignature {
+                sharps: *sharps as i8,
+                minor: *minor != 0,
+            },
+            (0x7F, data) => Self::SequencerSpecific(data.to_vec()),
+            (ty, data) => Self::Unknown {
+                ty,
+                data: data.to_vec(),
+            },
+        }
+    }
+}
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        let v = *self.data.get(self.pos)?;
+        self.pos += 1;
+        Some(v)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        let bytes = self.bytes(4)?;
+        Some(u32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    /// Variable-length quantity, at most 4 bytes long
+    fn vlq(&mut self) -> Option<u32> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Division, Format, MetaEvent, MidiFile, MidiPlayer, TrackEventKind};
+    use crate::test_util::sin_synth;
+    use crate::{MidiEvent, SynthDescriptor};
+
+    fn test_file() -> Vec<u8> {
+        let track: &[u8] = &[
+            // Tempo: 500000us per quarter
+            0x00, 0xFF, 0x51, 0x03, 0x07, 0xA1, 0x20, //
+            // NoteOn ch0 key 60
+            0x00, 0x90, 60, 100, //
+            // Running status NoteOn vel 0, after 480 ticks (0x83 0x60)
+            0x83, 0x60, 60, 0, //
+            // Tempo: 250000us per quarter
+            0x00, 0xFF, 0x51, 0x03, 0x03, 0xD0, 0x90, //
+            0x00, 0xC0, 5, //
+            0x83, 0x60, 0xFF, 0x2F, 0x00,
+        ];
+
+        let mut data = Vec::new();
+        data.extend_from_slice(b"MThd");
+        data.extend_from_slice(&6u32.to_be_bytes());
+        data.extend_from_slice(&[0, 0, 0, 1, 0x01, 0xE0]);
+        data.extend_from_slice(b"MTrk");
+        data.extend_from_slice(&(track.len() as u32).to_be_bytes());
+        data.extend_from_slice(track);
+        data
+    }
+
+    #[test]
+    fn parse() {
+        let file = MidiFile::parse(&test_file()).unwrap();
+
+        assert_eq!(file.format, Format::SingleTrack);
+        assert_eq!(file.division, Division::TicksPerQuarter(480));
+        assert_eq!(file.tracks.len(), 1);
+
+        let events = &file.tracks[0].events;
+        assert_eq!(events.len(), 6);
+        assert_eq!(
+            events[0].kind,
+            TrackEventKind::Meta(MetaEvent::Tempo(500000))
+        );
+        assert_eq!(events[2].delta, 480);
+        assert_eq!(
+            events[2].kind,
+            TrackEventKind::Midi(MidiEvent::NoteOn {
+                channel: 0,
+                key: 60,
+                vel: 0
+            })
+        );
+        assert_eq!(
+            events[4].kind,
+            TrackEventKind::Midi(MidiEvent::ProgramChange {
+                channel: 0,
+                program_id: 5
+            })
+        );
+    }
+
+    #[test]
+    fn play() {
+        let mut synth = sin_synth(SynthDescriptor::default());
+
+        let file = MidiFile::parse(&test_file()).unwrap();
+        let mut player = MidiPlayer::new(&file, &synth);
+
+        // 480 ticks at 120bpm + 480 ticks at 240bpm
+        assert_eq!(player.duration().as_millis(), 750);
+
+        let mut samples = vec![0f32; 44100 * 2];
+        player.render(&mut synth, &mut samples);
+
+        assert!(player.is_finished());
+        assert!(samples.iter().any(|s| *s != 0.0));
+
+        player.seek(&mut synth, std::time::Duration::from_millis(100));
+        assert!(!player.is_finished());
+        assert_eq!(player.position().as_millis(), 100);
+    }
+}
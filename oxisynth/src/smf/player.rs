@@ -0,0 +1,270 @@
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::{MidiEvent, Synth};
+
+use super::{Division, Format, MetaEvent, MidiFile, TrackEventKind};
+
+#[derive(Clone)]
+struct ScheduledEvent {
+    /// Time since the start of the song, in seconds
+    time: f64,
+    kind: TrackEventKind,
+}
+
+/// Plays a [`MidiFile`] on a [`Synth`]
+///
+/// The player keeps its own song position and sends the events of the file to the synth,
+/// splitting the [`Synth::write()`] calls at event boundaries.
+///
+/// ```ignore
+/// let file = MidiFile::load(&mut std::fs::File::open("song.mid")?)?;
+/// let mut player = MidiPlayer::new(&file, &synth);
+///
+/// let mut samples = [0f32; 1024];
+/// while !player.is_finished() {
+///     player.render(&mut synth, &mut samples);
+/// }
+/// ```
+#[derive(Clone)]
+pub struct MidiPlayer {
+    events: Vec<ScheduledEvent>,
+    duration: f64,
+
+    /// Index of the next event to send
+    next: usize,
+    /// Current song position in frames
+    frame: u64,
+    sample_rate: f64,
+
+    loop_range: Option<Range<f64>>,
+}
+
+impl MidiPlayer {
+    /// Create a player for `file`.
+    ///
+    /// The sample rate of `synth` is used to convert the song time to frames,
+    /// the player should be used with this synth (or one with the same sample rate).
+    pub fn new(file: &MidiFile, synth: &Synth) -> Self {
+        let (events, duration) = schedule(file);
+
+        Self {
+            events,
+            duration,
+            next: 0,
+            frame: 0,
+            sample_rate: synth.core.settings.sample_rate as f64,
+            loop_range: None,
+        }
+    }
+
+    /// Length of the song
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.duration)
+    }
+
+    /// Current song position
+    pub fn position(&self) -> Duration {
+        Duration::from_secs_f64(self.frame as f64 / self.sample_rate)
+    }
+
+    /// Returns `true` once all events were sent and no loop is set.
+    ///
+    /// Voices that are still releasing keep sounding on later writes.
+    pub fn is_finished(&self) -> bool {
+        self.loop_range.is_none() && self.next >= self.events.len()
+    }
+
+    /// Loop the given region of the song, or stop looping with `None`.
+    ///
+    /// Empty regions are ignored. To loop the whole song use `Duration::ZERO..player.duration()`.
+    pub fn set_loop(&mut self, range: Option<Range<Duration>>) {
+        self.loop_range = range
+            .map(|r| r.start.as_secs_f64()..r.end.as_secs_f64())
+            .filter(|r| r.start < r.end);
+    }
+
+    /// Jump to `position`.
+    ///
+    /// Sounding notes are stopped, and controller, program, pitch bend and SysEx messages
+    /// that happen before `position` are sent again, so that the channels are in the
+    /// same state as if the song was played from the start.
+    pub fn seek(&mut self, synth: &mut Synth, position: Duration) {
+        let frame = (position.as_secs_f64() * self.sample_rate).round() as u64;
+        self.seek_frame(synth, frame);
+    }
+
+    /// Render interleaved stereo samples, sending the song events as they come due.
+    pub fn render(&mut self, synth: &mut Synth, samples: &mut [f32]) {
+        let len = samples.len() / 2;
+        self.render_with(synth, len, |synth, range| {
+            synth.write(&mut samples[range.start * 2..range.end * 2]);
+        });
+    }
+
+    /// Render non-interleaved stereo samples, sending the song events as they come due.
+    pub fn render_split(&mut self, synth: &mut Synth, left: &mut [f32], right: &mut [f32]) {
+        let len = left.len().min(right.len());
+        self.render_with(synth, len, |synth, range| {
+            synth.write((&mut left[range.clone()], &mut right[range]));
+        });
+    }
+
+    /// Render `len` frames with `write`, sending the song events as they come due.
+    ///
+    /// `write` is called with the range of frames to render, it should render exactly that
+    /// many frames of `synth`, for example with [`Synth::write()`] or the writers of the
+    /// `wav` module.
+    pub fn render_with(
+        &mut self,
+        synth: &mut Synth,
+        len: usize,
+        mut write: impl FnMut(&mut Synth, Range<usize>),
+    ) {
+        let mut done = 0;
+
+        while done < len {
+            let loop_frames = self
+                .loop_range
+                .clone()
+                .map(|r| self.to_frame(r.start)..self.to_frame(r.end));
+
+            if let Some(loop_frames) = &loop_frames {
+                if self.frame >= loop_frames.end {
+                    self.seek_frame(synth, loop_frames.start);
+                }
+            }
+
+            // Send everything that is due
+            while let Some(event) = self.events.get(self.next) {
+                if self.to_frame(event.time) > self.frame {
+                    break;
+                }
+                send(synth, &event.kind);
+                self.next += 1;
+            }
+
+            // Render up to the next event, or the loop end
+            let mut chunk = (len - done) as u64;
+            if let Some(event) = self.events.get(self.next) {
+                chunk = chunk.min(self.to_frame(event.time) - self.frame);
+            }
+            if let Some(loop_frames) = &loop_frames {
+                if loop_frames.end > self.frame {
+                    chunk = chunk.min(loop_frames.end - self.frame);
+                }
+            }
+            let chunk = chunk as usize;
+
+            write(synth, done..done + chunk);
+
+            done += chunk;
+            self.frame += chunk as u64;
+        }
+    }
+}
+
+impl MidiPlayer {
+    fn to_frame(&self, time: f64) -> u64 {
+        (time * self.sample_rate).round() as u64
+    }
+
+    fn seek_frame(&mut self, synth: &mut Synth, frame: u64) {
+        for channel in 0..synth.channel_count() as u8 {
+            synth.send_event(MidiEvent::AllSoundOff { channel }).ok();
+            synth
+                .send_event(MidiEvent::ControlChange {
+                    channel,
+                    ctrl: 121,
+                    value: 0,
+                })
+                .ok();
+        }
+
+        // Chase the channel state up to the new position
+        self.next = 0;
+        while let Some(event) = self.events.get(self.next) {
+            if self.to_frame(event.time) >= frame {
+                break;
+            }
+
+            if !matches!(
+                event.kind,
+                TrackEventKind::Midi(MidiEvent::NoteOn { .. } | MidiEvent::NoteOff { .. })
+            ) {
+                send(synth, &event.kind);
+            }
+            self.next += 1;
+        }
+
+        self.frame = frame;
+    }
+}
+
+fn send(synth: &mut Synth, kind: &TrackEventKind) {
+    let res = match kind {
+        TrackEventKind::Midi(event) => synth.send_event(*event),
+        TrackEventKind::SysEx(data) => synth.send_sysex(data),
+        _ => Ok(()),
+    };
+
+    if let Err(err) = res {
+        log::trace!("Failed to send MIDI event: {err}");
+    }
+}
+
+/// Merge the tracks into one list of events, and convert ticks to seconds using the tempo map.
+///
+/// Returns the events and the length of the song in seconds.
+fn schedule(file: &MidiFile) -> (Vec<ScheduledEvent>, f64) {
+    let mut merged: Vec<(u64, &TrackEventKind)> = Vec::new();
+
+    let mut offset = 0;
+    for track in file.tracks.iter() {
+        let mut tick = offset;
+        for event in track.events.iter() {
+            tick += event.delta as u64;
+            merged.push((tick, &event.kind));
+        }
+
+        // Format 2 tracks are played one after another
+        if file.format == Format::Sequential {
+            offset = tick;
+        }
+    }
+
+    // Stable, so events on the same tick keep the track order
+    merged.sort_by_key(|(tick, _)| *tick);
+
+    let secs_per_tick = |tempo: u32| match file.division {
+        Division::TicksPerQuarter(tpq) => tempo as f64 / 1_000_000.0 / tpq as f64,
+        Division::Smpte {
+            fps,
+            ticks_per_frame,
+        } => 1.0 / (fps as f64 * ticks_per_frame.max(1) as f64),
+    };
+
+    // 120 BPM until the first tempo event (SMF 1.0)
+    let mut tick_len = secs_per_tick(500_000);
+    let mut last_tick = 0;
+    let mut time = 0.0;
+
+    let mut events = Vec::with_capacity(merged.len());
+    for (tick, kind) in merged {
+        time += (tick - last_tick) as f64 * tick_len;
+        last_tick = tick;
+
+        match kind {
+            TrackEventKind::Meta(MetaEvent::Tempo(tempo)) => {
+                tick_len = secs_per_tick(*tempo);
+            }
+            TrackEventKind::Meta(_) => {}
+            _ => events.push(ScheduledEvent {
+                time,
+                kind: kind.clone(),
+            }),
+        }
+    }
+
+    (events, time)
+}
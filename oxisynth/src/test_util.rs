@@ -0,0 +1,45 @@
+//! Setup shared by the unit tests
+
+use crate::{MidiEvent, SoundFont, Synth, SynthDescriptor};
+
+/// Synth with `testdata/sin.sf2` loaded
+pub(crate) fn sin_synth(desc: SynthDescriptor) -> Synth {
+    let mut synth = Synth::new(desc).unwrap();
+    let mut file = std::fs::File::open("../testdata/sin.sf2").unwrap();
+    synth.add_font(SoundFont::load(&mut file).unwrap(), true);
+    synth
+}
+
+/// Synth with `testdata/sin.sf2` loaded, playing middle C on channel 0
+pub(crate) fn playing_synth() -> Synth {
+    let mut synth = sin_synth(SynthDescriptor::default());
+    send(&mut synth, note_on(60));
+    synth
+}
+
+pub(crate) fn send(synth: &mut Synth, event: MidiEvent) {
+    synth.send_event(event).unwrap();
+}
+
+/// Control change on channel 0
+pub(crate) fn cc(ctrl: u8, value: u8) -> MidiEvent {
+    MidiEvent::ControlChange {
+        channel: 0,
+        ctrl,
+        value,
+    }
+}
+
+/// Note on channel 0, with a velocity of 100
+pub(crate) fn note_on(key: u8) -> MidiEvent {
+    MidiEvent::NoteOn {
+        channel: 0,
+        key,
+        vel: 100,
+    }
+}
+
+/// Note off on channel 0
+pub(crate) fn note_off(key: u8) -> MidiEvent {
+    MidiEvent::NoteOff { channel: 0, key }
+}
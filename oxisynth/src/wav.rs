@@ -0,0 +1,298 @@
+//! WAV and raw PCM writers, for offline rendering
+//!
+//! [`WavWriter`] streams the output of a [`Synth`] to a stereo WAV file, and fixes up the
+//! header once rendering ends. [`PcmWriter`] writes the same samples without a header.
+//!
+//! ```ignore
+//! let file = std::fs::File::create("out.wav")?;
+//! let mut wav = WavWriter::new(file, &synth, SampleFormat::I16)?;
+//!
+//! synth.send_event(MidiEvent::NoteOn { channel: 0, key: 60, vel: 100 })?;
+//! wav.write(&mut synth, 44100)?;
+//! wav.finish()?;
+//! ```
+
+use std::io::{self, Seek, SeekFrom, Write};
+
+use crate::Synth;
+
+/// Frames rendered at once
+const CHUNK_FRAMES: usize = 1024;
+/// Size of the header written by [`WavWriter`]
+const HEADER_LEN: u64 = 44;
+
+/// Format of the written samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SampleFormat {
+    /// 16-bit signed integers, dithered as by [`Synth::write_i16()`]
+    #[default]
+    I16,
+    /// 24-bit signed integers
+    I24,
+    /// 32-bit floating-point numbers
+    F32,
+}
+
+impl SampleFormat {
+    fn bytes(self) -> usize {
+        match self {
+            Self::I16 => 2,
+            Self::I24 => 3,
+            Self::F32 => 4,
+        }
+    }
+}
+
+/// Writes the output of a [`Synth`] as interleaved little-endian stereo samples, without header
+pub struct PcmWriter<W: Write> {
+    writer: W,
+    format: SampleFormat,
+
+    f32_buf: Vec<f32>,
+    i16_buf: Vec<i16>,
+    bytes: Vec<u8>,
+
+    /// Number of bytes of samples written
+    len: u64,
+}
+
+impl<W: Write> PcmWriter<W> {
+    pub fn new(writer: W, format: SampleFormat) -> Self {
+        Self {
+            writer,
+            format,
+            f32_buf: vec![0.0; CHUNK_FRAMES * 2],
+            i16_buf: vec![0; CHUNK_FRAMES * 2],
+            bytes: Vec::with_capacity(CHUNK_FRAMES * 2 * format.bytes()),
+            len: 0,
+        }
+    }
+
+    pub fn format(&self) -> SampleFormat {
+        self.format
+    }
+
+    /// Number of frames written
+    pub fn frames(&self) -> u64 {
+        self.len / (2 * self.format.bytes() as u64)
+    }
+
+    /// Render `frames` frames of `synth` and write them
+    pub fn write(&mut self, synth: &mut Synth, frames: usize) -> io::Result<()> {
+        let mut left = frames;
+        while left > 0 {
+            let len = left.min(CHUNK_FRAMES);
+            self.bytes.clear();
+
+            match self.format {
+                SampleFormat::I16 => {
+                    let buf = &mut self.i16_buf[..len * 2];
+                    synth.write(&mut buf[..]);
+                    self.bytes.extend(buf.iter().flat_map(|s| s.to_le_bytes()));
+                }
+                SampleFormat::I24 => {
+                    let buf = &mut self.f32_buf[..len * 2];
+                    synth.write(&mut buf[..]);
+                    self.bytes.extend(buf.iter().flat_map(|s| {
+                        let s = (s * 8_388_607.0).round().clamp(-8_388_608.0, 8_388_607.0);
+                        let [b0, b1, b2, _] = (s as i32).to_le_bytes();
+                        [b0, b1, b2]
+                    }));
+                }
+                SampleFormat::F32 => {
+                    let buf = &mut self.f32_buf[..len * 2];
+                    synth.write(&mut buf[..]);
+                    self.bytes.extend(buf.iter().flat_map(|s| s.to_le_bytes()));
+                }
+            }
+
+            self.writer.write_all(&self.bytes)?;
+            self.len += self.bytes.len() as u64;
+            left -= len;
+        }
+
+        Ok(())
+    }
+
+    /// Flush and return the underlying writer
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Writes the output of a [`Synth`] to a stereo WAV file
+///
+/// The sizes in the header are fixed up by [`WavWriter::finish()`], or when the writer is
+/// dropped, ignoring errors in that case.
+pub struct WavWriter<W: Write + Seek> {
+    pcm: Option<PcmWriter<W>>,
+    sample_rate: u32,
+    /// Position of the header in the writer
+    start: u64,
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    /// Write the header of a file at the sample rate of `synth`
+    pub fn new(mut writer: W, synth: &Synth, format: SampleFormat) -> io::Result<Self> {
+        let sample_rate = synth.core.settings.sample_rate.round() as u32;
+        let start = writer.stream_position()?;
+        writer.write_all(&header(sample_rate, format, 0))?;
+
+        Ok(Self {
+            pcm: Some(PcmWriter::new(writer, format)),
+            sample_rate,
+            start,
+        })
+    }
+
+    pub fn format(&self) -> SampleFormat {
+        self.pcm().format()
+    }
+
+    /// Number of frames written
+    pub fn frames(&self) -> u64 {
+        self.pcm().frames()
+    }
+
+    /// Render `frames` frames of `synth` and write them
+    ///
+    /// Returns an error, before anything is written, if the file would get over the 4 GiB
+    /// limit of the format.
+    pub fn write(&mut self, synth: &mut Synth, frames: usize) -> io::Result<()> {
+        let pcm = self.pcm_mut();
+        let len = pcm.len + (frames * 2 * pcm.format.bytes()) as u64;
+        if len + HEADER_LEN - 8 > u32::MAX as u64 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "WAV file size limit reached",
+            ));
+        }
+
+        pcm.write(synth, frames)
+    }
+
+    /// Fix up the header and return the underlying writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.update_header()?;
+        self.pcm.take().unwrap().into_inner()
+    }
+
+    fn update_header(&mut self) -> io::Result<()> {
+        let start = self.start;
+        let sample_rate = self.sample_rate;
+        let pcm = self.pcm_mut();
+
+        pcm.writer.seek(SeekFrom::Start(start))?;
+        pcm.writer
+            .write_all(&header(sample_rate, pcm.format, pcm.len as u32))?;
+        pcm.writer
+            .seek(SeekFrom::Start(start + HEADER_LEN + pcm.len))?;
+        Ok(())
+    }
+
+    fn pcm(&self) -> &PcmWriter<W> {
+        self.pcm.as_ref().unwrap()
+    }
+
+    fn pcm_mut(&mut self) -> &mut PcmWriter<W> {
+        self.pcm.as_mut().unwrap()
+    }
+}
+
+impl<W: Write + Seek> Drop for WavWriter<W> {
+    fn drop(&mut self) {
+        if self.pcm.is_some() {
+            self.update_header().ok();
+            self.pcm_mut().writer.flush().ok();
+        }
+    }
+}
+
+/// RIFF header of a stereo file with `data_len` bytes of samples
+fn header(sample_rate: u32, format: SampleFormat, data_len: u32) -> [u8; HEADER_LEN as usize] {
+    const CHANNELS: u16 = 2;
+    const WAVE_FORMAT_PCM: u16 = 1;
+    const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+
+    let tag = match format {
+        SampleFormat::I16 | SampleFormat::I24 => WAVE_FORMAT_PCM,
+        SampleFormat::F32 => WAVE_FORMAT_IEEE_FLOAT,
+    };
+    let block_align = CHANNELS * format.bytes() as u16;
+
+    let mut header = [0; HEADER_LEN as usize];
+    let fields: [&[u8]; 13] = [
+        b"RIFF",
+        &(data_len + HEADER_LEN as u32 - 8).to_le_bytes(),
+        b"WAVE",
+        b"fmt ",
+        &16u32.to_le_bytes(),
+        &tag.to_le_bytes(),
+        &CHANNELS.to_le_bytes(),
+        &sample_rate.to_le_bytes(),
+        &(sample_rate * block_align as u32).to_le_bytes(),
+        &block_align.to_le_bytes(),
+        &(format.bytes() as u16 * 8).to_le_bytes(),
+        b"data",
+        &data_len.to_le_bytes(),
+    ];
+
+    let mut pos = 0;
+    for field in fields {
+        header[pos..pos + field.len()].copy_from_slice(field);
+        pos += field.len();
+    }
+
+    header
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::{PcmWriter, SampleFormat, WavWriter};
+    use crate::test_util::playing_synth;
+
+    #[test]
+    fn wav_writer() {
+        let u16_at = |data: &[u8], pos: usize| u16::from_le_bytes([data[pos], data[pos + 1]]);
+        let u32_at =
+            |data: &[u8], pos: usize| u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap());
+
+        for (format, tag, bits) in [
+            (SampleFormat::I16, 1, 16),
+            (SampleFormat::I24, 1, 24),
+            (SampleFormat::F32, 3, 32),
+        ] {
+            let mut synth = playing_synth();
+            let mut wav = WavWriter::new(Cursor::new(Vec::new()), &synth, format).unwrap();
+            wav.write(&mut synth, 1500).unwrap();
+            wav.write(&mut synth, 500).unwrap();
+            assert_eq!(wav.frames(), 2000);
+            let data = wav.finish().unwrap().into_inner();
+
+            let data_len = 2000 * 2 * bits / 8;
+            assert_eq!(data.len(), 44 + data_len);
+            assert_eq!(&data[0..4], b"RIFF");
+            assert_eq!(u32_at(&data, 4) as usize, 36 + data_len);
+            assert_eq!(u16_at(&data, 20), tag);
+            assert_eq!(u16_at(&data, 22), 2);
+            assert_eq!(u32_at(&data, 24), 44100);
+            assert_eq!(u16_at(&data, 34) as usize, bits);
+            assert_eq!(&data[36..40], b"data");
+            assert_eq!(u32_at(&data, 40) as usize, data_len);
+            assert!(data[44..].iter().any(|b| *b != 0));
+        }
+
+        // Same samples as a float write
+        let mut pcm = PcmWriter::new(Vec::new(), SampleFormat::F32);
+        pcm.write(&mut playing_synth(), 2000).unwrap();
+        let data = pcm.into_inner().unwrap();
+
+        let mut samples = vec![0.0f32; 4000];
+        playing_synth().write(&mut samples[..]);
+        let expected: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        assert_eq!(data, expected);
+    }
+}
@@ -30,6 +30,9 @@ pub enum Error {
     UnexpectedMemberOfSampleData(Chunk),
 
     MissingChunk(MissingChunk),
+
+    /// The hydra has more records than its 16-bit indices can address
+    TooManyHydraRecords(usize),
 }
 
 #[derive(Debug)]
@@ -10,11 +10,12 @@ pub use error::Error;
 mod riff;
 
 use raw::{
-    Bag, Generator, GeneratorAmountRange, GeneratorType, Info, InstrumentHeader, Modulator,
-    PresetHeader, RawSoundFontData, SampleData, SampleHeader,
+    Bag, Generator, GeneratorAmount, GeneratorAmountRange, GeneratorType, Hydra, Info,
+    InstrumentHeader, Modulator, ModulatorTransform, PresetHeader, RawSoundFontData, SampleData,
+    SampleHeader, SampleLink,
 };
 
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, Write};
 
 #[derive(Debug)]
 pub struct Preset {
@@ -125,8 +126,8 @@ impl SoundFont2 {
 
                 let zone_items = get_zones(zones, modulators, generators, start, end);
 
-                // Ignore Terminator
-                if header.name != "EOS" {
+                // Ignore Terminator, named EOI by the spec but EOS was checked before
+                if header.name != "EOI" && header.name != "EOS" {
                     list.push(Instrument {
                         header: header.clone(),
                         zones: zone_items,
@@ -199,6 +200,147 @@ impl SoundFont2 {
         });
         self
     }
+
+    /// Writes the font as a RIFF sfbk file.
+    ///
+    /// [`SampleData`] only refers to the sample chunks of the file the font was loaded from,
+    /// so their contents are passed as `smpl` and `sm24`, see [`raw::SampleChunk::read()`].
+    pub fn write<W: Write>(
+        &self,
+        writer: &mut W,
+        smpl: &[u8],
+        sm24: Option<&[u8]>,
+    ) -> Result<(), Error> {
+        raw::write_sfbk(writer, &self.info, &self.to_hydra()?, smpl, sm24)
+    }
+
+    /// Flattens the presets, instruments and samples back into a hydra, with its terminal records
+    fn to_hydra(&self) -> Result<Hydra, Error> {
+        fn index(len: usize) -> Result<u16, Error> {
+            u16::try_from(len).map_err(|_| Error::TooManyHydraRecords(len))
+        }
+
+        fn push_zones(
+            zones: &[Zone],
+            bags: &mut Vec<Bag>,
+            modulators: &mut Vec<Modulator>,
+            generators: &mut Vec<Generator>,
+        ) -> Result<(), Error> {
+            for zone in zones {
+                bags.push(Bag {
+                    generator_id: index(generators.len())?,
+                    modulator_id: index(modulators.len())?,
+                });
+                modulators.extend_from_slice(&zone.mod_list);
+                generators.extend_from_slice(&zone.gen_list);
+            }
+            Ok(())
+        }
+
+        // "The terminal record conventionally contains zero in all fields"
+        let zone_terminal = Zone {
+            mod_list: vec![Modulator {
+                src: 0.into(),
+                dest: GeneratorType::StartAddrsOffset,
+                amount: 0,
+                amt_src: 0.into(),
+                transform: ModulatorTransform::Linear,
+            }],
+            gen_list: vec![Generator {
+                ty: SfEnum::Value(GeneratorType::StartAddrsOffset),
+                amount: GeneratorAmount::I16(0),
+            }],
+        };
+
+        let mut preset_headers = Vec::new();
+        let mut preset_bags = Vec::new();
+        let mut preset_modulators = Vec::new();
+        let mut preset_generators = Vec::new();
+
+        for preset in self.presets.iter() {
+            preset_headers.push(PresetHeader {
+                bag_id: index(preset_bags.len())?,
+                ..preset.header.clone()
+            });
+            push_zones(
+                &preset.zones,
+                &mut preset_bags,
+                &mut preset_modulators,
+                &mut preset_generators,
+            )?;
+        }
+        preset_headers.push(PresetHeader {
+            name: "EOP".into(),
+            preset: 0,
+            bank: 0,
+            bag_id: index(preset_bags.len())?,
+            library: 0,
+            genre: 0,
+            morphology: 0,
+        });
+        push_zones(
+            std::slice::from_ref(&zone_terminal),
+            &mut preset_bags,
+            &mut preset_modulators,
+            &mut preset_generators,
+        )?;
+
+        let mut instrument_headers = Vec::new();
+        let mut instrument_bags = Vec::new();
+        let mut instrument_modulators = Vec::new();
+        let mut instrument_generators = Vec::new();
+
+        for instrument in self.instruments.iter() {
+            instrument_headers.push(InstrumentHeader {
+                bag_id: index(instrument_bags.len())?,
+                ..instrument.header.clone()
+            });
+            push_zones(
+                &instrument.zones,
+                &mut instrument_bags,
+                &mut instrument_modulators,
+                &mut instrument_generators,
+            )?;
+        }
+        instrument_headers.push(InstrumentHeader {
+            name: "EOI".into(),
+            bag_id: index(instrument_bags.len())?,
+        });
+        push_zones(
+            std::slice::from_ref(&zone_terminal),
+            &mut instrument_bags,
+            &mut instrument_modulators,
+            &mut instrument_generators,
+        )?;
+
+        let mut sample_headers = self.sample_headers.clone();
+        sample_headers.push(SampleHeader {
+            name: "EOS".into(),
+            start: 0,
+            end: 0,
+            loop_start: 0,
+            loop_end: 0,
+            sample_rate: 0,
+            origpitch: 0,
+            pitchadj: 0,
+            sample_link: 0,
+            sample_type: SampleLink::None,
+        });
+
+        Ok(Hydra {
+            preset_headers,
+            preset_bags,
+            preset_modulators,
+            preset_generators,
+
+            instrument_headers,
+            instrument_bags,
+            instrument_modulators,
+            instrument_generators,
+
+            sample_headers,
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -256,3 +398,129 @@ impl<T: PartialEq, RAW> PartialEq<T> for SfEnum<T, RAW> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::raw::{Bag, Generator, GeneratorAmount, Modulator, RawSoundFontData};
+    use crate::SoundFont2;
+
+    /// Checks that `bag_ids` point to the first bag of each zone list, and that the terminal
+    /// records follow them
+    fn check_zones(
+        bag_ids: &[u16],
+        zone_lens: &[usize],
+        bags: &[Bag],
+        modulators: &[Modulator],
+        generators: &[Generator],
+    ) {
+        let mut bag_id = 0;
+        for (id, len) in bag_ids.iter().zip(zone_lens) {
+            assert_eq!(*id as usize, bag_id);
+            bag_id += len;
+        }
+        assert_eq!(bag_ids.len(), zone_lens.len() + 1);
+        assert_eq!(*bag_ids.last().unwrap() as usize, bag_id);
+        assert_eq!(bags.len(), bag_id + 1);
+
+        let terminal = bags.last().unwrap();
+        assert_eq!(terminal.modulator_id as usize, modulators.len() - 1);
+        assert_eq!(terminal.generator_id as usize, generators.len() - 1);
+        assert_eq!(modulators.last().unwrap().amount, 0);
+        assert!(matches!(
+            generators.last().unwrap().amount,
+            GeneratorAmount::I16(0)
+        ));
+    }
+
+    #[test]
+    fn to_hydra() {
+        let mut file = std::fs::File::open("../testdata/Boomwhacker.sf2").unwrap();
+        let font = SoundFont2::load(&mut file).unwrap();
+        let hydra = font.to_hydra().unwrap();
+
+        assert_eq!(hydra.preset_headers.last().unwrap().name, "EOP");
+        assert_eq!(hydra.instrument_headers.last().unwrap().name, "EOI");
+        assert_eq!(hydra.sample_headers.last().unwrap().name, "EOS");
+        assert_eq!(hydra.sample_headers.len(), font.sample_headers.len() + 1);
+
+        let bag_ids: Vec<_> = hydra.preset_headers.iter().map(|h| h.bag_id).collect();
+        let zone_lens: Vec<_> = font.presets.iter().map(|p| p.zones.len()).collect();
+        check_zones(
+            &bag_ids,
+            &zone_lens,
+            &hydra.preset_bags,
+            &hydra.preset_modulators,
+            &hydra.preset_generators,
+        );
+
+        let bag_ids: Vec<_> = hydra.instrument_headers.iter().map(|h| h.bag_id).collect();
+        let zone_lens: Vec<_> = font.instruments.iter().map(|i| i.zones.len()).collect();
+        check_zones(
+            &bag_ids,
+            &zone_lens,
+            &hydra.instrument_bags,
+            &hydra.instrument_modulators,
+            &hydra.instrument_generators,
+        );
+    }
+
+    #[test]
+    fn terminal_records() {
+        let data = std::fs::read("../testdata/sin.sf2").unwrap();
+        let load = || RawSoundFontData::load(&mut std::io::Cursor::new(&data)).unwrap();
+
+        let raw = load();
+        let counts = [
+            raw.hydra.preset_headers.len(),
+            raw.hydra.instrument_headers.len(),
+            raw.hydra.sample_headers.len(),
+        ];
+        let font = SoundFont2::from_raw(raw);
+        assert_eq!(
+            [
+                font.presets.len(),
+                font.instruments.len(),
+                font.sample_headers.len()
+            ],
+            counts.map(|len| len - 1)
+        );
+
+        // Only the terminal records are dropped, not the last one
+        let mut raw = load();
+        raw.hydra.sample_headers.pop();
+        let len = raw.hydra.sample_headers.len();
+        assert_eq!(SoundFont2::from_raw(raw).sample_headers.len(), len);
+    }
+
+    #[test]
+    fn write() {
+        for name in ["sin.sf2", "Boomwhacker.sf2", "Boomwhacker.sf3"] {
+            let data = std::fs::read(format!("../testdata/{name}")).unwrap();
+            let mut file = std::io::Cursor::new(&data);
+            let font = SoundFont2::load(&mut file).unwrap();
+            let smpl = font.sample_data.smpl.unwrap().read(&mut file).unwrap();
+
+            let mut written = Vec::new();
+            font.write(&mut written, &smpl, None).unwrap();
+            // As for `RawSoundFontData`, only the file that follows the spec is kept byte for byte
+            if name == "Boomwhacker.sf2" {
+                assert!(data == written, "{name} is not written as is");
+            }
+
+            let reloaded = SoundFont2::load(&mut std::io::Cursor::new(&written)).unwrap();
+            assert_eq!(format!("{:?}", font.info), format!("{:?}", reloaded.info));
+            assert_eq!(
+                format!("{:?}", font.presets),
+                format!("{:?}", reloaded.presets)
+            );
+            assert_eq!(
+                format!("{:?}", font.instruments),
+                format!("{:?}", reloaded.instruments)
+            );
+            assert_eq!(
+                format!("{:?}", font.sample_headers),
+                format!("{:?}", reloaded.sample_headers)
+            );
+        }
+    }
+}
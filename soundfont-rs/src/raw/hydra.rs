@@ -34,11 +34,12 @@ pub use preset::*;
 #[allow(unused_imports)]
 pub use sample::*;
 
+use super::utils::Writer;
 use crate::error::MissingChunk;
-use crate::riff::{Chunk, ScratchReader};
+use crate::riff::{self, Chunk, ScratchReader};
 use crate::{error::Error, riff::ChunkId};
 
-use std::io::{Read, Seek};
+use std::io::{self, Read, Seek};
 
 #[derive(Debug)]
 pub struct Hydra {
@@ -119,4 +120,71 @@ impl Hydra {
             sample_headers: sample_headers.ok_or(SampleHeaders)?,
         })
     }
+
+    /// Writes the sub-chunks of the pdta list
+    pub(crate) fn write(&self) -> io::Result<Vec<u8>> {
+        fn records<T>(
+            out: &mut Vec<u8>,
+            id: ChunkId,
+            records: &[T],
+            write: fn(&T, &mut Writer),
+        ) -> io::Result<()> {
+            let mut writer = Writer::new();
+            for record in records {
+                write(record, &mut writer);
+            }
+            riff::write_chunk(out, id, &writer.into_inner())
+        }
+
+        let mut out = Vec::new();
+
+        records(
+            &mut out,
+            ChunkId::phdr,
+            &self.preset_headers,
+            PresetHeader::write,
+        )?;
+        records(&mut out, ChunkId::pbag, &self.preset_bags, Bag::write)?;
+        records(
+            &mut out,
+            ChunkId::pmod,
+            &self.preset_modulators,
+            Modulator::write,
+        )?;
+        records(
+            &mut out,
+            ChunkId::pgen,
+            &self.preset_generators,
+            Generator::write,
+        )?;
+
+        records(
+            &mut out,
+            ChunkId::inst,
+            &self.instrument_headers,
+            InstrumentHeader::write,
+        )?;
+        records(&mut out, ChunkId::ibag, &self.instrument_bags, Bag::write)?;
+        records(
+            &mut out,
+            ChunkId::imod,
+            &self.instrument_modulators,
+            Modulator::write,
+        )?;
+        records(
+            &mut out,
+            ChunkId::igen,
+            &self.instrument_generators,
+            Generator::write,
+        )?;
+
+        records(
+            &mut out,
+            ChunkId::shdr,
+            &self.sample_headers,
+            SampleHeader::write,
+        )?;
+
+        Ok(out)
+    }
 }
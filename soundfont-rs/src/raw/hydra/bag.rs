@@ -3,7 +3,7 @@ use crate::{
     riff::{ChunkId, ScratchReader},
 };
 
-use super::super::utils::Reader;
+use super::super::utils::{Reader, Writer};
 use crate::riff::Chunk;
 use std::io::{Read, Seek};
 
@@ -24,6 +24,11 @@ impl Bag {
         })
     }
 
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        writer.write_u16(self.generator_id);
+        writer.write_u16(self.modulator_id);
+    }
+
     pub(crate) fn read_all(
         pbag: &Chunk,
         file: &mut ScratchReader<impl Read + Seek>,
@@ -1,4 +1,4 @@
-use super::super::utils::Reader;
+use super::super::utils::{Reader, Writer};
 use crate::error::Error;
 use crate::riff::{Chunk, ChunkId, ScratchReader};
 use crate::SfEnum;
@@ -87,6 +87,19 @@ impl Generator {
         Ok(Self { ty, amount })
     }
 
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        writer.write_u16(self.ty.as_raw());
+
+        match self.amount {
+            GeneratorAmount::I16(v) => writer.write_i16(v),
+            GeneratorAmount::U16(v) => writer.write_u16(v),
+            GeneratorAmount::Range(range) => {
+                writer.write_u8(range.low);
+                writer.write_u8(range.high);
+            }
+        }
+    }
+
     pub(crate) fn read_all(
         pmod: &Chunk,
         file: &mut ScratchReader<impl Read + Seek>,
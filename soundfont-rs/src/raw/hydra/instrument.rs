@@ -1,4 +1,4 @@
-use super::super::utils::Reader;
+use super::super::utils::{Reader, Writer};
 use crate::riff::{Chunk, ScratchReader};
 use crate::{error::Error, riff::ChunkId};
 use std::io::{Read, Seek};
@@ -17,6 +17,11 @@ impl InstrumentHeader {
         Ok(Self { name, bag_id })
     }
 
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        writer.write_string(&self.name, 20);
+        writer.write_u16(self.bag_id);
+    }
+
     pub(crate) fn read_all(
         phdr: &Chunk,
         file: &mut ScratchReader<impl Read + Seek>,
@@ -1,7 +1,7 @@
 use crate::error::Error;
 use crate::raw::GeneratorType;
 
-use super::super::utils::Reader;
+use super::super::utils::{Reader, Writer};
 use crate::riff::{Chunk, ChunkId, ScratchReader};
 use std::io::{Read, Seek};
 
@@ -124,6 +124,18 @@ impl From<u8> for SourceType {
     }
 }
 
+impl From<SourceType> for u8 {
+    fn from(ty: SourceType) -> Self {
+        match ty {
+            SourceType::Linear => 0,
+            SourceType::Concave => 1,
+            SourceType::Convex => 2,
+            SourceType::Switch => 3,
+            SourceType::Unknown(v) => v,
+        }
+    }
+}
+
 /// 8.2  Modulator Source Enumerators
 /// Flags telling the polarity of a modulator.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -228,6 +240,24 @@ impl From<u16> for ModulatorSource {
     }
 }
 
+impl From<ModulatorSource> for u16 {
+    fn from(src: ModulatorSource) -> Self {
+        let mut out = src.index as u16 & 0b1111111;
+
+        if let ControllerPalette::Midi(_) = src.controller_palette {
+            out |= 1 << 7;
+        }
+        if src.direction == SourceDirection::Negative {
+            out |= 1 << 8;
+        }
+        if src.polarity == SourcePolarity::Bipolar {
+            out |= 1 << 9;
+        }
+
+        out | (u8::from(src.ty) as u16 & 0b111111) << 10
+    }
+}
+
 #[allow(dead_code)]
 /// 8.3  Modulator Transform Enumerators
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -255,6 +285,15 @@ impl TryFrom<u16> for ModulatorTransform {
     }
 }
 
+impl From<ModulatorTransform> for u16 {
+    fn from(transform: ModulatorTransform) -> Self {
+        match transform {
+            ModulatorTransform::Linear => 0,
+            ModulatorTransform::Absolute => 2,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Modulator {
     pub src: ModulatorSource,
@@ -291,6 +330,14 @@ impl Modulator {
         })
     }
 
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        writer.write_u16(self.src.into());
+        writer.write_u16(self.dest as u16);
+        writer.write_i16(self.amount);
+        writer.write_u16(self.amt_src.into());
+        writer.write_u16(self.transform.into());
+    }
+
     pub(crate) fn read_all(
         pmod: &Chunk,
         file: &mut ScratchReader<impl Read + Seek>,
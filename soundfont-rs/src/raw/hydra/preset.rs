@@ -1,4 +1,4 @@
-use super::super::utils::Reader;
+use super::super::utils::{Reader, Writer};
 use crate::riff::{Chunk, ScratchReader};
 use crate::{error::Error, riff::ChunkId};
 
@@ -44,6 +44,17 @@ impl PresetHeader {
         })
     }
 
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        writer.write_string(&self.name, 20);
+        writer.write_u16(self.preset);
+        writer.write_u16(self.bank);
+        writer.write_u16(self.bag_id);
+
+        writer.write_u32(self.library);
+        writer.write_u32(self.genre);
+        writer.write_u32(self.morphology);
+    }
+
     pub(crate) fn read_all(
         phdr: &Chunk,
         file: &mut ScratchReader<impl Read + Seek>,
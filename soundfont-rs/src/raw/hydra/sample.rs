@@ -1,4 +1,4 @@
-use super::super::utils::Reader;
+use super::super::utils::{Reader, Writer};
 use crate::riff::{Chunk, ScratchReader};
 use crate::{error::Error, riff::ChunkId};
 use std::io::{Read, Seek};
@@ -78,6 +78,22 @@ impl SampleHeader {
         })
     }
 
+    pub(crate) fn write(&self, writer: &mut Writer) {
+        writer.write_string(&self.name, 20);
+
+        writer.write_u32(self.start);
+        writer.write_u32(self.end);
+        writer.write_u32(self.loop_start);
+        writer.write_u32(self.loop_end);
+
+        writer.write_u32(self.sample_rate);
+
+        writer.write_u8(self.origpitch);
+        writer.write_i8(self.pitchadj);
+        writer.write_u16(self.sample_link);
+        writer.write_u16(self.sample_type as u16);
+    }
+
     pub(crate) fn read_all(
         phdr: &Chunk,
         file: &mut ScratchReader<impl Read + Seek>,
@@ -1,9 +1,9 @@
 use super::utils::Reader;
 use crate::error::MissingChunk;
-use crate::riff::{Chunk, ScratchReader};
+use crate::riff::{self, Chunk, ScratchReader};
 use crate::{error::Error, riff::ChunkId};
 
-use std::io::{Read, Seek};
+use std::io::{self, Read, Seek};
 
 #[derive(Debug)]
 pub struct Version {
@@ -18,6 +18,12 @@ impl Version {
             minor: u16::from_le_bytes([bytes[2], bytes[3]]),
         }
     }
+
+    fn to_bytes(&self) -> [u8; 4] {
+        let [a, b] = self.major.to_le_bytes();
+        let [c, d] = self.minor.to_le_bytes();
+        [a, b, c, d]
+    }
 }
 
 /// Supplemental Information
@@ -167,4 +173,46 @@ impl Info {
             software,
         })
     }
+
+    /// Writes the sub-chunks of the INFO list
+    pub(crate) fn write(&self) -> io::Result<Vec<u8>> {
+        // Zero terminated, and padded to an even size
+        fn string(string: &str) -> Vec<u8> {
+            let mut data = string.as_bytes().to_vec();
+            data.push(0);
+            if !data.len().is_multiple_of(2) {
+                data.push(0);
+            }
+            data
+        }
+
+        let mut out = Vec::new();
+
+        riff::write_chunk(&mut out, ChunkId::ifil, &self.version.to_bytes())?;
+        riff::write_chunk(&mut out, ChunkId::isng, &string(&self.sound_engine))?;
+        riff::write_chunk(&mut out, ChunkId::INAM, &string(&self.bank_name))?;
+
+        if let Some(rom_name) = &self.rom_name {
+            riff::write_chunk(&mut out, ChunkId::irom, &string(rom_name))?;
+        }
+        if let Some(rom_version) = &self.rom_version {
+            riff::write_chunk(&mut out, ChunkId::iver, &rom_version.to_bytes())?;
+        }
+
+        let optional = [
+            (ChunkId::ICRD, &self.creation_date),
+            (ChunkId::IENG, &self.engineers),
+            (ChunkId::IPRD, &self.product),
+            (ChunkId::ICOP, &self.copyright),
+            (ChunkId::ICMT, &self.comments),
+            (ChunkId::ISFT, &self.software),
+        ];
+        for (id, value) in optional {
+            if let Some(value) = value {
+                riff::write_chunk(&mut out, id, &string(value))?;
+            }
+        }
+
+        Ok(out)
+    }
 }
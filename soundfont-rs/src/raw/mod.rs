@@ -1,4 +1,4 @@
-//! A low-level SoundFont™ file reader
+//! A low-level SoundFont™ file reader and writer
 //!
 //! This performs no postprocessing, it just presents the data from the file as is.
 //!
@@ -17,7 +17,7 @@ use crate::{
     error::{Error, MissingChunk},
     riff::{self, ChunkId},
 };
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, Write};
 
 pub use hydra::*;
 pub use info::*;
@@ -72,3 +72,94 @@ impl RawSoundFontData {
         })
     }
 }
+
+impl RawSoundFontData {
+    /// Writes the font as a RIFF sfbk file, the hydra is written as is.
+    ///
+    /// [`SampleData`] only refers to the sample chunks of the file the font was loaded from,
+    /// so their contents are passed as `smpl` and `sm24`, see [`SampleChunk::read()`].
+    pub fn write<W: Write>(
+        &self,
+        writer: &mut W,
+        smpl: &[u8],
+        sm24: Option<&[u8]>,
+    ) -> Result<(), Error> {
+        write_sfbk(writer, &self.info, &self.hydra, smpl, sm24)
+    }
+}
+
+pub(crate) fn write_sfbk<W: Write>(
+    writer: &mut W,
+    info: &Info,
+    hydra: &Hydra,
+    smpl: &[u8],
+    sm24: Option<&[u8]>,
+) -> Result<(), Error> {
+    let info = info.write()?;
+    let pdta = hydra.write()?;
+
+    let sdta_len =
+        4 + riff::chunk_size(smpl.len()) + sm24.map_or(0, |sm24| riff::chunk_size(sm24.len()));
+    let len = 4
+        + riff::chunk_size(4 + info.len())
+        + riff::chunk_size(sdta_len)
+        + riff::chunk_size(4 + pdta.len());
+
+    riff::write_header(writer, ChunkId::RIFF, len)?;
+    writer.write_all(ChunkId::sfbk.as_bytes())?;
+
+    riff::write_list(writer, ChunkId::INFO, &info)?;
+
+    riff::write_header(writer, ChunkId::LIST, sdta_len)?;
+    writer.write_all(ChunkId::sdta.as_bytes())?;
+    riff::write_chunk(writer, ChunkId::smpl, smpl)?;
+    if let Some(sm24) = sm24 {
+        riff::write_chunk(writer, ChunkId::sm24, sm24)?;
+    }
+
+    riff::write_list(writer, ChunkId::pdta, &pdta)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::RawSoundFontData;
+
+    /// Loads a font with the contents of its sample chunks
+    fn load(data: &[u8]) -> (RawSoundFontData, Vec<u8>, Option<Vec<u8>>) {
+        let mut file = Cursor::new(data);
+        let font = RawSoundFontData::load(&mut file).unwrap();
+        let smpl = font.sample_data.smpl.unwrap().read(&mut file).unwrap();
+        let sm24 = font
+            .sample_data
+            .sm24
+            .map(|sm24| sm24.read(&mut file).unwrap());
+        (font, smpl, sm24)
+    }
+
+    #[test]
+    fn write() {
+        for name in ["sin.sf2", "Boomwhacker.sf2", "Boomwhacker.sf3"] {
+            let original = std::fs::read(format!("../testdata/{name}")).unwrap();
+            let (font, smpl, sm24) = load(&original);
+
+            let mut written = Vec::new();
+            font.write(&mut written, &smpl, sm24.as_deref()).unwrap();
+
+            // The order of the INFO sub-chunks and the optional ones are not kept,
+            // so only check the file that follows the spec byte for byte
+            if name == "Boomwhacker.sf2" {
+                assert!(original == written, "{name} is not written as is");
+            }
+
+            let (reloaded, reloaded_smpl, reloaded_sm24) = load(&written);
+            assert_eq!(format!("{:?}", font.info), format!("{:?}", reloaded.info));
+            assert_eq!(format!("{:?}", font.hydra), format!("{:?}", reloaded.hydra));
+            assert!(smpl == reloaded_smpl);
+            assert_eq!(sm24, reloaded_sm24);
+        }
+    }
+}
@@ -1,9 +1,9 @@
 use crate::riff::Chunk;
 use crate::{error::Error, riff::ChunkId};
 
-use std::io::{Read, Seek};
+use std::io::{self, Read, Seek, SeekFrom};
 
-/// Sample can be read like so:
+/// Sample can be read with [`SampleChunk::read()`], or like so:
 /// ```ignore
 /// file.seek(SeekFrom::Start(chunk.offset))?;
 ///
@@ -23,6 +23,15 @@ impl SampleChunk {
             len: chunk.len(),
         }
     }
+
+    /// Reads the contents of the chunk from the file it was loaded from.
+    pub fn read<F: Read + Seek>(&self, file: &mut F) -> io::Result<Vec<u8>> {
+        file.seek(SeekFrom::Start(self.offset))?;
+
+        let mut buff = vec![0u8; self.len as usize];
+        file.read_exact(&mut buff)?;
+        Ok(buff)
+    }
 }
 
 /// The Sample Binary Data
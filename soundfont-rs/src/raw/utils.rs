@@ -84,3 +84,48 @@ impl<'a> Reader<'a> {
         Ok(i16::from_le_bytes(out))
     }
 }
+
+#[derive(Default)]
+pub(crate) struct Writer {
+    data: Vec<u8>,
+}
+impl Writer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Writes `string` in a field of `len` bytes, truncated or padded with zeros
+    pub fn write_string(&mut self, string: &str, len: usize) {
+        let mut end = string.len().min(len);
+        while !string.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        self.data.extend_from_slice(&string.as_bytes()[..end]);
+        self.data.resize(self.data.len() + len - end, 0);
+    }
+
+    pub fn write_u8(&mut self, v: u8) {
+        self.data.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_u16(&mut self, v: u16) {
+        self.data.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, v: u32) {
+        self.data.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_i8(&mut self, v: i8) {
+        self.data.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn write_i16(&mut self, v: i16) {
+        self.data.extend_from_slice(&v.to_le_bytes());
+    }
+}
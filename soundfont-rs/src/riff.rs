@@ -1,10 +1,10 @@
-//! Utilities for reading RIFF-formatted files
+//! Utilities for reading and writing RIFF-formatted files
 
 // (Based on `riff` MIT crate: Copyright 2018 Francesco Bertolaccini)
 
 use std::{
     fmt,
-    io::{Read, Seek, SeekFrom},
+    io::{self, Read, Seek, SeekFrom, Write},
 };
 
 pub struct ScratchReader<T> {
@@ -138,6 +138,12 @@ impl ChunkId {
     ];
 }
 
+impl ChunkId {
+    pub fn as_bytes(&self) -> &[u8; 4] {
+        &self.0
+    }
+}
+
 impl fmt::Debug for ChunkId {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> fmt::Result {
         if let Ok(v) = std::str::from_utf8(&self.0) {
@@ -268,3 +274,36 @@ impl Chunk {
         }
     }
 }
+
+/// Size of a chunk with `len` bytes of contents, including its header and padding.
+pub fn chunk_size(len: usize) -> usize {
+    8 + len + len % 2
+}
+
+/// Writes the header of a chunk with `len` bytes of contents.
+pub fn write_header<W: Write>(writer: &mut W, id: ChunkId, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "RIFF chunk over 4 GiB"))?;
+
+    writer.write_all(&id.0)?;
+    writer.write_all(&len.to_le_bytes())
+}
+
+/// Writes a chunk with `data` as its contents, padded to an even size.
+pub fn write_chunk<W: Write>(writer: &mut W, id: ChunkId, data: &[u8]) -> io::Result<()> {
+    write_header(writer, id, data.len())?;
+    writer.write_all(data)?;
+
+    if !data.len().is_multiple_of(2) {
+        writer.write_all(&[0])?;
+    }
+
+    Ok(())
+}
+
+/// Writes a `LIST` chunk of type `ty`, with `children` as its already written sub-chunks.
+pub fn write_list<W: Write>(writer: &mut W, ty: ChunkId, children: &[u8]) -> io::Result<()> {
+    write_header(writer, ChunkId::LIST, 4 + children.len())?;
+    writer.write_all(&ty.0)?;
+    writer.write_all(children)
+}